@@ -7,6 +7,36 @@ const SMALL: u32 = 16;
 const MEDIUM: u32 = 32;
 const BIG: u32 = 128;
 
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts the allocations made by the benches, so that maps can be compared by how often they
+/// allocate as well as by speed.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[cfg(feature = "smallvec")]
+fn allocations_during<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
 mod vec {
     use super::*;
     use vector_map::VecMap as Map;
@@ -132,6 +162,116 @@ mod vec {
     fn bench_get_none_big(b: &mut test::Bencher) {
         get_none(b, BIG);
     }
+
+    #[bench]
+    fn bench_from_iter_1024(b: &mut test::Bencher) {
+        b.iter(|| test::black_box((0..1024u32).map(|i| (i, i)).collect::<Map<_, _>>()))
+    }
+    #[bench]
+    fn bench_from_unique_iter_1024(b: &mut test::Bencher) {
+        b.iter(|| test::black_box(unsafe { Map::from_unique_iter((0..1024u32).map(|i| (i, i))) }))
+    }
+
+    #[bench]
+    fn bench_get_middle_1024(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..1024).map(|i| (i, i)).collect();
+        b.iter(|| test::black_box(map.get(&512)))
+    }
+    #[bench]
+    fn bench_sorted_get_middle_1024(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..1024).map(|i| (i, i)).collect();
+        let sorted = map.as_sorted();
+        b.iter(|| test::black_box(sorted.get(&512)))
+    }
+
+    #[bench]
+    fn bench_eq_permutation_1000(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+        let reversed: Map<u32, u32> = (0..1000).rev().map(|i| (i, i)).collect();
+        b.iter(|| test::black_box(map == reversed))
+    }
+    #[bench]
+    fn bench_eq_sorted_permutation_1000(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+        let reversed: Map<u32, u32> = (0..1000).rev().map(|i| (i, i)).collect();
+        b.iter(|| test::black_box(map.eq_sorted(&reversed)))
+    }
+
+    #[bench]
+    fn bench_get_middle_256(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..256).map(|i| (i, i)).collect();
+        b.iter(|| test::black_box(map.get(&128)))
+    }
+    #[bench]
+    fn bench_frozen_get_middle_256(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..256).rev().map(|i| (i, i)).collect();
+        let frozen = map.freeze();
+        b.iter(|| test::black_box(frozen.get(&128)))
+    }
+    #[bench]
+    fn bench_frozen_get_middle_1024(b: &mut test::Bencher) {
+        let map: Map<u32, u32> = (0..1024).rev().map(|i| (i, i)).collect();
+        let frozen = map.freeze();
+        b.iter(|| test::black_box(frozen.get(&512)))
+    }
+}
+
+#[cfg(feature = "smallvec")]
+mod small {
+    use super::*;
+    use vector_map::VecMap;
+    const INLINE: usize = 16;
+    type Map<K, V> = vector_map::small::SmallVecMap<K, V, INLINE>;
+
+    fn fill_small(num: u32) -> Map<u32, u32> {
+        let mut map = Map::new();
+        for i in 0..num {
+            map.insert(i, i);
+        }
+        for i in (0..num).step_by(2) {
+            map.remove(&i);
+        }
+        map
+    }
+
+    fn fill_vec(num: u32) -> VecMap<u32, u32> {
+        let mut map = VecMap::new();
+        for i in 0..num {
+            map.insert(i, i);
+        }
+        for i in (0..num).step_by(2) {
+            map.remove(&i);
+        }
+        map
+    }
+
+    /// Compares how many times filling a `SmallVecMap` and a `VecMap` allocates, printing the
+    /// counts (visible with `cargo bench -- --nocapture`), then times the allocations of the former.
+    fn allocations(b: &mut test::Bencher, num: u32) {
+        let small = allocations_during(|| drop(test::black_box(fill_small(num))));
+        let vec = allocations_during(|| drop(test::black_box(fill_vec(num))));
+        eprintln!(
+            "{} entries: {} allocations for SmallVecMap<_, _, {}>, {} for VecMap",
+            num, small, INLINE, vec
+        );
+        if num as usize <= INLINE {
+            assert_eq!(small, 0);
+        }
+        b.iter(|| allocations_during(|| drop(test::black_box(fill_small(num)))))
+    }
+
+    #[bench]
+    fn bench_allocations_small(b: &mut test::Bencher) {
+        allocations(b, SMALL);
+    }
+    #[bench]
+    fn bench_allocations_medium(b: &mut test::Bencher) {
+        allocations(b, MEDIUM);
+    }
+    #[bench]
+    fn bench_allocations_big(b: &mut test::Bencher) {
+        allocations(b, BIG);
+    }
 }
 
 mod linear {
@@ -387,3 +527,37 @@ mod hash {
         get_none(b, BIG);
     }
 }
+
+#[cfg(feature = "serde_impl")]
+mod serde_impl {
+    use super::*;
+    use vector_map::set::VecSet;
+
+    #[bench]
+    fn bench_deserialize_hintless_set(b: &mut test::Bencher) {
+        let json = serde_json::to_string(&(0..BIG).collect::<Vec<u32>>()).unwrap();
+        b.iter(|| test::black_box(serde_json::from_str::<VecSet<u32>>(&json).unwrap()))
+    }
+
+    fn unique_map_json(len: u32) -> String {
+        let map =
+            unsafe { vector_map::VecMap::from_unique_iter((0..len).map(|i| (i.to_string(), i))) };
+        serde_json::to_string(&map).unwrap()
+    }
+    #[bench]
+    fn bench_deserialize_checked_10k(b: &mut test::Bencher) {
+        let json = unique_map_json(10_000);
+        b.iter(|| {
+            test::black_box(serde_json::from_str::<vector_map::VecMap<String, u32>>(&json).unwrap())
+        })
+    }
+    #[bench]
+    fn bench_deserialize_trusted_10k(b: &mut test::Bencher) {
+        use vector_map::serde::TrustedVecMap;
+        let json = unique_map_json(10_000);
+        b.iter(|| {
+            let trusted = serde_json::from_str::<TrustedVecMap<String, u32>>(&json).unwrap();
+            test::black_box(unsafe { trusted.into_inner() })
+        })
+    }
+}
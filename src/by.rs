@@ -0,0 +1,215 @@
+use crate::{remove_at, Iter, IterMut, Keys, Values};
+use alloc::vec::Vec;
+
+/// Decides whether two keys of a [`VecMapBy`] are the same key.
+///
+/// It is implemented for closures taking two keys, so a comparator doesn't need a type of its own.
+/// Like `PartialEq`, it should be symmetric, and the map assumes no two of its keys are equal.
+pub trait Comparator<K: ?Sized> {
+    /// Returns `true` if `a` and `b` are the same key.
+    ///
+    /// It isn't named `eq`, so that it doesn't collide with `PartialEq::eq` on comparators that
+    /// implement both.
+    fn compare_eq(&self, a: &K, b: &K) -> bool;
+}
+
+impl<K: ?Sized, F: Fn(&K, &K) -> bool> Comparator<K> for F {
+    #[inline]
+    fn compare_eq(&self, a: &K, b: &K) -> bool {
+        self(a, b)
+    }
+}
+
+/// A `VecMap` variant whose keys are compared by a user-supplied [`Comparator`] instead of
+/// `PartialEq`, such as an epsilon comparison for floating-point keys.
+///
+/// The comparator is stored in the map, and used by every key search.
+#[derive(Clone, Default)]
+pub struct VecMapBy<K, V, C> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    comparator: C,
+}
+
+impl<K, V, C: Comparator<K>> VecMapBy<K, V, C> {
+    pub fn new() -> Self
+    where
+        C: Default,
+    {
+        Self::with_comparator(C::default())
+    }
+
+    pub fn with_comparator(comparator: C) -> Self {
+        Self::with_capacity_and_comparator(0, comparator)
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, comparator: C) -> Self {
+        VecMapBy {
+            keys: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            comparator,
+        }
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity().min(self.values.capacity())
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    #[inline]
+    fn position(&self, key: &K) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|k| self.comparator.compare_eq(key, k))
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.position(key).is_some()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.position(key).map(|p| &self.values[p])
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.position(key).map(move |p| &mut self.values[p])
+    }
+
+    /// Returns the stored key the comparator considers equal to `key`, along with its value.
+    pub fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+        self.position(key).map(|p| (&self.keys[p], &self.values[p]))
+    }
+
+    /// Inserts the entry, overwriting and returning the value of the key the comparator considers
+    /// equal to `key`, if any. That key is kept, while `key` is dropped.
+    pub fn insert(&mut self, key: K, mut value: V) -> Option<V> {
+        if let Some(position) = self.position(&key) {
+            core::mem::swap(&mut value, &mut self.values[position]);
+            Some(value)
+        } else {
+            self.keys.push(key);
+            self.values.push(value);
+            None
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Removes the entry of the key the comparator considers equal to `key`, following the same
+    /// ordering rules as [`VecMap::remove`](../struct.VecMap.html#method.remove).
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        let index = self.position(key)?;
+        Some(remove_at(&mut self.keys, &mut self.values, index))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.keys.iter().zip(self.values.iter()),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.keys.iter().zip(self.values.iter_mut()),
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            iter: self.keys.iter(),
+            _phantom: Default::default(),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            iter: self.values.iter(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug, C> core::fmt::Debug for VecMapBy<K, V, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map()
+            .entries(self.keys.iter().zip(self.values.iter()))
+            .finish()
+    }
+}
+
+impl<K, V, C: Comparator<K>> Extend<(K, V)> for VecMapBy<K, V, C> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, C: Comparator<K>> IntoIterator for &'a VecMapBy<K, V, C> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[test]
+fn epsilon_keys() {
+    // Comparable itself, without getting in the way of the comparator's method.
+    #[derive(Default, PartialEq)]
+    struct Epsilon;
+    impl Comparator<f64> for Epsilon {
+        fn compare_eq(&self, a: &f64, b: &f64) -> bool {
+            (a - b).abs() < 1e-6
+        }
+    }
+
+    assert!(Epsilon.compare_eq(&0.3, &(0.1 + 0.2)));
+    assert!(Epsilon.eq(&Epsilon));
+
+    let mut map: VecMapBy<f64, &str, Epsilon> = VecMapBy::new();
+    assert_eq!(map.insert(0.1 + 0.2, "a"), None);
+    assert_eq!(map.insert(0.3, "b"), Some("a"));
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&0.3001), None);
+    assert_eq!(map.get(&0.300_000_000_1), Some(&"b"));
+    assert_eq!(map.get_key_value(&0.3), Some((&(0.1 + 0.2), &"b")));
+
+    map.insert(1.0, "c");
+    *map.get_mut(&1.000_000_000_1).unwrap() = "d";
+    assert_eq!(map.remove(&0.999_999_999_9), Some("d"));
+    assert!(!map.contains_key(&1.0));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn closure_comparator() {
+    let mut map = VecMapBy::with_comparator(|a: &&str, b: &&str| a.eq_ignore_ascii_case(b));
+    map.extend(vec![("Key", 1), ("KEY", 2), ("other", 3)]);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&"key"), Some(&2));
+    assert!(map.keys().copied().eq(vec!["Key", "other"]));
+}
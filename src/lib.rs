@@ -1,13 +1,148 @@
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+pub mod by;
+pub mod multi;
+#[cfg(feature = "rayon")]
+pub mod rayon;
 #[cfg(feature = "serde_impl")]
 pub mod serde;
 pub mod set;
+#[cfg(feature = "smallvec")]
+pub mod small;
 
+use alloc::borrow::ToOwned;
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
 use contracts::*;
-use std::{
-    iter::FromIterator,
+use core::{
+    iter::{FromIterator, FusedIterator},
     ops::{Index, IndexMut},
 };
 
+mod sealed {
+    pub trait Sealed<K: ?Sized> {}
+
+    impl<Q: PartialEq<K> + ?Sized, K: ?Sized> Sealed<K> for Q {}
+}
+
+/// Key equivalence used by all of `VecMap`'s key lookups, implemented for any `Q: PartialEq<K>`.
+///
+/// This blanket implementation is the only one: it can't coexist with another one based on
+/// [`Borrow`](core::borrow::Borrow), as some types are both comparable to and borrowable from a key type.
+/// Wrap a query in [`Borrowed`] to compare it through the key type's `Borrow` implementation instead.
+///
+/// The trait is sealed, so that how lookups compare keys can evolve without breaking downstream
+/// implementations.
+pub trait Equivalent<K: ?Sized>: sealed::Sealed<K> {
+    fn equivalent(&self, key: &K) -> bool;
+}
+
+impl<Q: PartialEq<K> + ?Sized, K: ?Sized> Equivalent<K> for Q {
+    #[inline]
+    fn equivalent(&self, key: &K) -> bool {
+        self == key
+    }
+}
+
+/// A lookup query compared to keys through their [`Borrow`](core::borrow::Borrow) implementation, like
+/// `HashMap` lookups are.
+///
+/// This is useful for queries that `PartialEq` can't compare to the key type, such as slices for boxed
+/// slice keys.
+///
+/// ```
+/// use vector_map::{Borrowed, VecMap};
+///
+/// let mut map: VecMap<Box<[u8]>, u32> = VecMap::new();
+/// map.insert(vec![1, 2].into_boxed_slice(), 12);
+/// assert_eq!(map.get(&Borrowed(&[1, 2][..])), Some(&12));
+/// assert_eq!(map.remove(&Borrowed(&[1, 2][..])), Some(12));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Borrowed<'a, Q: ?Sized>(pub &'a Q);
+
+impl<'a, Q: PartialEq + ?Sized, K: core::borrow::Borrow<Q>> PartialEq<K> for Borrowed<'a, Q> {
+    #[inline]
+    fn eq(&self, key: &K) -> bool {
+        self.0 == key.borrow()
+    }
+}
+
+/// The vectors this crate's maps store their keys and values in, so that they can all remove entries
+/// through [`remove_at`] and [`retain_in_place`].
+pub(crate) trait Storage<T> {
+    fn as_mut_slice(&mut self) -> &mut [T];
+    fn truncate(&mut self, len: usize);
+    fn remove(&mut self, index: usize) -> T;
+    fn swap_remove(&mut self, index: usize) -> T;
+}
+
+impl<T> Storage<T> for Vec<T> {
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        Vec::as_mut_slice(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> T {
+        Vec::remove(self, index)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        Vec::swap_remove(self, index)
+    }
+}
+
+/// Removes the entry at `index`, moving the last entry into its place, unless the `ordered` feature
+/// is enabled, in which case the following entries are shifted instead.
+#[inline]
+pub(crate) fn remove_at<K, V>(
+    keys: &mut impl Storage<K>,
+    values: &mut impl Storage<V>,
+    index: usize,
+) -> (K, V) {
+    if cfg!(feature = "ordered") {
+        (keys.remove(index), values.remove(index))
+    } else {
+        (keys.swap_remove(index), values.swap_remove(index))
+    }
+}
+
+/// Keeps the entries for which `f` returns `true`, also passing it their index before any removal.
+///
+/// The kept entries are swapped to the front of the vectors in a single pass, keeping their order,
+/// and the others are dropped by truncating the vectors once the pass is over. If `f` panics, no entry
+/// is lost, but the visited ones may have been reordered.
+pub(crate) fn retain_in_place<K, V, F: FnMut(usize, &mut K, &mut V) -> bool>(
+    keys: &mut impl Storage<K>,
+    values: &mut impl Storage<V>,
+    mut f: F,
+) {
+    let mut write = 0;
+    {
+        let (keys, values) = (keys.as_mut_slice(), values.as_mut_slice());
+        for read in 0..keys.len() {
+            if f(read, &mut keys[read], &mut values[read]) {
+                if read != write {
+                    keys.swap(read, write);
+                    values.swap(read, write);
+                }
+                write += 1;
+            }
+        }
+    }
+    keys.truncate(write);
+    values.truncate(write);
+}
+
 /// A std::vec::Vec based Map, motivated by the fact that, for some key types,
 /// iterating over a vector can be faster than other methods for small maps.
 ///
@@ -26,12 +161,27 @@ use std::{
 ///
 /// Checking equality between maps is defined as "both maps are the same set", and performs worst
 /// for maps that are permutations of each other.
-#[derive(Clone, Default)]
+#[derive(Default)]
 pub struct VecMap<K, V> {
     keys: Vec<K>,
     values: Vec<V>,
 }
 
+impl<K: Clone, V: Clone> Clone for VecMap<K, V> {
+    fn clone(&self) -> Self {
+        VecMap {
+            keys: self.keys.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    /// Reuses `self`'s allocations when their capacity suffices.
+    fn clone_from(&mut self, source: &Self) {
+        self.keys.clone_from(&source.keys);
+        self.values.clone_from(&source.values);
+    }
+}
+
 // #[invariant(self.keys.len() == self.values.len())]
 impl<K, V> VecMap<K, V> {
     #[post(ret.len() == 0)]
@@ -65,512 +215,3113 @@ impl<K, V> VecMap<K, V> {
         self.keys.capacity().min(self.values.capacity())
     }
 
+    /// Removes all entries, keeping the allocated capacity for reuse, like `Vec::clear`.
+    ///
+    /// Use [`clear_and_release`](#method.clear_and_release) to free the memory as well.
     // #[post(self.len() == 0)]
     pub fn clear(&mut self) {
         self.keys.clear();
         self.values.clear();
     }
 
+    /// Removes all entries and frees the allocated memory.
+    ///
+    /// This suits long-lived maps that are periodically emptied, which [`clear`](#method.clear) would
+    /// leave holding on to their largest allocation.
+    pub fn clear_and_release(&mut self) {
+        self.keys = Vec::new();
+        self.values = Vec::new();
+    }
+
     #[inline]
-    fn position<Q: PartialEq<K> + ?Sized>(&self, key: &Q) -> Option<usize> {
-        self.keys.iter().position(|k| key == k)
+    fn position<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> Option<usize> {
+        self.keys.iter().position(|k| key.equivalent(k))
     }
 
-    pub fn contains_key<Q: PartialEq<K> + ?Sized>(&self, key: &Q) -> bool {
+    pub fn contains_key<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> bool {
         self.position(key).is_some()
     }
 
+    /// Returns `true` if some key maps to `value`.
+    ///
+    /// Like key lookups, this is O(n).
+    pub fn contains_value<Q: PartialEq<V> + ?Sized>(&self, value: &Q) -> bool {
+        self.values.iter().any(|v| value == v)
+    }
+
+    /// Returns a key that maps to `value`, if any.
+    ///
+    /// This is O(n), and if several keys map to `value`, which one is returned is unspecified.
+    pub fn find_key<Q: PartialEq<V> + ?Sized>(&self, value: &Q) -> Option<&K> {
+        self.values
+            .iter()
+            .position(|v| value == v)
+            .map(|p| &self.keys[p])
+    }
+
+    /// Returns the first entry, in iteration order, for which `f` returns `true`.
+    pub fn find<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> Option<(&K, &V)> {
+        self.iter().find(|(k, v)| f(k, v))
+    }
+
+    /// Returns the first entry, in iteration order, for which `f` returns `true`, with a mutable
+    /// reference to its value.
+    pub fn find_mut<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) -> Option<(&K, &mut V)> {
+        self.iter_mut().find(|(k, v)| f(k, v))
+    }
+
+    /// Returns the number of entries for which `f` returns `true`.
+    pub fn count<F: FnMut(&K, &V) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|(k, v)| f(k, v)).count()
+    }
+
+    /// Returns the index of `key`'s entry in the underlying vectors.
+    ///
+    /// Indices are invalidated by removals: [`remove`](#method.remove) moves the last entry into the removed one's place,
+    /// or, with the `ordered` feature, shifts the entries that followed it back by one.
+    pub fn index_of<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> Option<usize> {
+        self.position(key)
+    }
+
+    /// Returns the entry at `index` in the underlying vectors.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.keys.get(index).zip(self.values.get(index))
+    }
+
+    /// Returns the entry at `index` in the underlying vectors, with a mutable reference to the value.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.keys.get(index).zip(self.values.get_mut(index))
+    }
+
+    /// Returns the entry at `index` in the underlying vectors, without bounds checking.
+    /// # Safety
+    /// Calling this with an `index` that isn't lower than `self.len()` is undefined behavior,
+    /// as with `slice::get_unchecked`.
+    pub unsafe fn get_unchecked(&self, index: usize) -> (&K, &V) {
+        (
+            self.keys.get_unchecked(index),
+            self.values.get_unchecked(index),
+        )
+    }
+
+    /// Returns the entry at `index` in the underlying vectors, with a mutable reference to the value,
+    /// without bounds checking.
+    /// # Safety
+    /// Calling this with an `index` that isn't lower than `self.len()` is undefined behavior,
+    /// as with `slice::get_unchecked_mut`.
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> (&K, &mut V) {
+        (
+            self.keys.get_unchecked(index),
+            self.values.get_unchecked_mut(index),
+        )
+    }
+
     #[post(!self.contains_key(key) -> ret.is_none())]
     #[post(self.contains_key(key) -> ret.is_some())]
-    pub fn get<'l, Q: PartialEq<K> + ?Sized>(&'l self, key: &Q) -> Option<&'l V> {
+    pub fn get<'l, Q: Equivalent<K> + ?Sized>(&'l self, key: &Q) -> Option<&'l V> {
         self.position(key).map(|p| &self.values[p])
     }
 
+    /// Returns the value stored for `key`, or `default` if there is none.
+    pub fn get_or<'l, Q: Equivalent<K> + ?Sized>(&'l self, key: &Q, default: &'l V) -> &'l V {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Returns the value stored for `key`, or the result of `default` if there is none.
+    /// `default` is only called if the key is absent.
+    pub fn get_or_else<'l, Q: Equivalent<K> + ?Sized, F: FnOnce() -> &'l V>(
+        &'l self,
+        key: &Q,
+        default: F,
+    ) -> &'l V {
+        self.get(key).unwrap_or_else(default)
+    }
+
+    /// Looks up each of `keys`, returning the results in the same order.
+    ///
+    /// Each lookup being O(n), this is O(n * m) for `m` keys.
+    pub fn get_many<Q: Equivalent<K>>(&self, keys: &[Q]) -> Vec<Option<&V>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
     #[post(!old(self.contains_key(key)) -> ret.is_none())]
     #[post(old(self.contains_key(key)) -> ret.is_some())]
-    pub fn get_mut<'l, Q: PartialEq<K> + ?Sized>(&'l mut self, key: &Q) -> Option<&'l mut V> {
+    pub fn get_mut<'l, Q: Equivalent<K> + ?Sized>(&'l mut self, key: &Q) -> Option<&'l mut V> {
         self.position(key).map(move |p| &mut self.values[p])
     }
 
+    /// Returns mutable references to the values of two distinct keys at once.
+    ///
+    /// Returns `None` if either key is missing, or if both designate the same entry.
+    ///
+    /// ```
+    /// use vector_map::VecMap;
+    ///
+    /// let mut map: VecMap<_, _> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    /// if let Some((a, b)) = map.get_pair_mut2(&"a", &"b") {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// assert_eq!(map[&"a"], 2);
+    /// assert_eq!(map[&"b"], 1);
+    /// ```
+    pub fn get_pair_mut2<Q1, Q2>(&mut self, a: &Q1, b: &Q2) -> Option<(&mut V, &mut V)>
+    where
+        Q1: Equivalent<K> + ?Sized,
+        Q2: Equivalent<K> + ?Sized,
+    {
+        let a = self.position(a)?;
+        let b = self.position(b)?;
+        if a < b {
+            let (left, right) = self.values.split_at_mut(b);
+            Some((&mut left[a], &mut right[0]))
+        } else if b < a {
+            let (left, right) = self.values.split_at_mut(a);
+            Some((&mut right[0], &mut left[b]))
+        } else {
+            None
+        }
+    }
+
     #[post(!old(self.contains_key(&key)) -> ret.is_none())]
     #[post(old(self.contains_key(&key)) -> ret.is_some())]
-    pub fn insert(&mut self, key: K, mut value: V) -> Option<V>
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        K: PartialEq,
+    {
+        let previous = self.insert_full(key, value).1;
+        #[cfg(debug_assertions)]
+        self.check_invariants();
+        previous
+    }
+
+    /// Like [`insert`](#method.insert), but also returns the index of the entry in the underlying vectors:
+    /// its existing position if `key` was already present, or the end of the vectors otherwise.
+    #[post(self.get_index(ret.0).is_some())]
+    pub fn insert_full(&mut self, key: K, mut value: V) -> (usize, Option<V>)
     where
         K: PartialEq,
     {
         if let Some(position) = self.position(&key) {
-            std::mem::swap(&mut value, &mut self.values[position]);
-            Some(value)
+            core::mem::swap(&mut value, &mut self.values[position]);
+            (position, Some(value))
         } else {
             self.keys.push(key);
             self.values.push(value);
-            None
+            (self.len() - 1, None)
         }
     }
 
-    pub fn drain(&mut self) -> Drain<K, V> {
-        Drain {
-            iter: self.keys.drain(..).zip(self.values.drain(..)),
+    /// Inserts a new entry at `index` in the underlying vectors, shifting the following entries.
+    ///
+    /// If `key` is already present, its value is overwritten in place and returned, and `index` is
+    /// ignored: the entry keeps its current position.
+    ///
+    /// Returns an error if `index` is greater than the map's length, whether `key` is present
+    /// or not.
+    pub fn insert_at(
+        &mut self,
+        index: usize,
+        key: K,
+        mut value: V,
+    ) -> Result<Option<V>, InsertAtError>
+    where
+        K: PartialEq,
+    {
+        if index > self.len() {
+            return Err(InsertAtError {
+                index,
+                len: self.len(),
+            });
+        }
+        if let Some(position) = self.position(&key) {
+            core::mem::swap(&mut value, &mut self.values[position]);
+            Ok(Some(value))
+        } else {
+            self.keys.insert(index, key);
+            self.values.insert(index, value);
+            Ok(None)
         }
     }
 
-    pub fn reserve(&mut self, additional: usize) {
-        self.keys.reserve(additional);
-        self.values.reserve(additional);
+    /// Like [`insert`](#method.insert), but also returns whether `key` was newly added to the map.
+    #[post(ret.1 == ret.0.is_none())]
+    pub fn insert_checked(&mut self, key: K, value: V) -> (Option<V>, bool)
+    where
+        K: PartialEq,
+    {
+        let previous = self.insert(key, value);
+        let added = previous.is_none();
+        (previous, added)
     }
 
-    pub fn shrink_to_fit(&mut self) {
-        self.keys.shrink_to_fit();
-        self.values.shrink_to_fit();
+    /// Returns `true` if no key appears more than once in the map, which always holds unless the map was
+    /// misused through its unsafe API.
+    ///
+    /// This is an O(n²) check, meant for tests and debugging.
+    pub fn has_unique_keys(&self) -> bool
+    where
+        K: PartialEq,
+    {
+        self.keys
+            .iter()
+            .enumerate()
+            .all(|(i, key)| !self.keys[i + 1..].contains(key))
     }
 
-    #[post(!self.contains_key(key) -> ret.is_none())]
-    #[post(self.contains_key(key) -> ret.is_some())]
-    pub fn get_key_value<'l, Q: PartialEq<K> + ?Sized>(&'l self, key: &Q) -> Option<(&'l K, &'l V)> {
-        self.position(key).map(|p| (&self.keys[p], &self.values[p]))
+    /// Returns the keys that appear more than once in the map, each reported once at its first
+    /// occurrence. The result is empty unless the map was misused through its unsafe API.
+    ///
+    /// Like [`has_unique_keys`](#method.has_unique_keys), this is an O(n²) diagnostic.
+    pub fn duplicate_keys(&self) -> Vec<&K>
+    where
+        K: PartialEq,
+    {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(i, key)| !self.keys[..*i].contains(key) && self.keys[i + 1..].contains(key))
+            .map(|(_, key)| key)
+            .collect()
     }
 
-    #[post(!old(self.contains_key(key)) -> ret.is_none())]
-    #[post(old(self.contains_key(key)) -> ret.is_some())]
-    #[post(self.contains_key(key) == false)]
-    pub fn remove<Q: PartialEq<K> + ?Sized>(&mut self, key: &Q) -> Option<V> {
-        if let Some(index) = self.position(key) {
-            self.keys.swap_remove(index);
-            Some(self.values.swap_remove(index))
-        } else {
-            None
+    /// Panics if the map's invariants don't hold, which can only happen if it was misused through its
+    /// unsafe API: its keys must be unique, and match its values one to one.
+    ///
+    /// The check only runs in builds with `debug_assertions`, where it is O(n²), and does nothing
+    /// otherwise. [`insert`](#method.insert) and [`push_insert`](#method.push_insert) run it after
+    /// each insertion.
+    pub fn check_invariants(&self)
+    where
+        K: PartialEq,
+    {
+        debug_assert_eq!(
+            self.keys.len(),
+            self.values.len(),
+            "keys and values are out of step"
+        );
+        debug_assert!(self.has_unique_keys(), "the map has duplicate keys");
+    }
+
+    /// Appends the entry to the map without checking whether `key` is already present.
+    /// # Safety
+    /// The caller must ensure that `key` isn't already in the map: inserting a duplicate key breaks the
+    /// map's invariants, and only one of the duplicates will then be reachable through lookups.
+    /// In debug builds, a duplicate key makes [`check_invariants`](#method.check_invariants) panic.
+    pub unsafe fn push_insert(&mut self, key: K, value: V)
+    where
+        K: PartialEq,
+    {
+        self.keys.push(key);
+        self.values.push(value);
+        #[cfg(debug_assertions)]
+        self.check_invariants();
+    }
+
+    /// Builds a map from pairs whose keys are known to be unique, skipping the per-insertion key search.
+    ///
+    /// In debug builds, uniqueness is still checked, and a duplicate key causes a panic.
+    /// # Safety
+    /// The caller must ensure that `iter` yields no duplicate keys, as with [`push_insert`](#method.push_insert).
+    pub unsafe fn from_unique_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self
+    where
+        K: PartialEq,
+    {
+        let iterator = iter.into_iter();
+        let mut this = Self::with_capacity(iterator.size_hint().0);
+        for (key, value) in iterator {
+            debug_assert!(
+                !this.contains_key(&key),
+                "from_unique_iter received a duplicate key"
+            );
+            this.push_insert(key, value);
         }
+        this
     }
 
-    pub fn entry(&mut self, key: K) -> Entry<K, V>
+    /// Copies the pairs of `slice` into the map, overwriting the values of keys that are already
+    /// present. As with [`extend`](#method.extend), the last value wins for repeated keys.
+    pub fn extend_from_slice(&mut self, slice: &[(K, V)])
     where
-        K: PartialEq
+        K: PartialEq + Copy,
+        V: Copy,
     {
-        match self
-            .keys()
-            .enumerate()
-            .find(|(_, k)|  &&key == k)
-            .map(|(n, _)| n) {
-                Some(index) => Entry::Occupied(OccupiedEntry{ map: self, index }),
-                None => Entry::Vacant(VacantEntry{ map: self, key }),
-            }
+        self.extend(slice.iter().copied());
     }
 
-    #[post(!old(self.contains_key(key)) -> ret.is_none())]
-    #[post(old(self.contains_key(key)) -> ret.is_some())]
-    #[post(self.contains_key(key) == false)]
-    pub fn remove_entry<Q: PartialEq<K> + ?Sized>(&mut self, key: &Q) -> Option<(K, V)> {
-        if let Some(index) = self.position(key) {
-            Some((self.keys.swap_remove(index), self.values.swap_remove(index)))
-        } else {
-            None
+    /// Copies the pairs of `slice` into the map, skipping the per-insertion key search.
+    ///
+    /// In debug builds, uniqueness is still checked, and a duplicate key causes a panic.
+    /// # Safety
+    /// The caller must ensure that `slice` holds no duplicate keys and no key already in the map,
+    /// as with [`push_insert`](#method.push_insert).
+    pub unsafe fn extend_from_unique_slice(&mut self, slice: &[(K, V)])
+    where
+        K: PartialEq + Copy,
+        V: Copy,
+    {
+        self.reserve(slice.len());
+        for &(key, value) in slice {
+            debug_assert!(
+                !self.contains_key(&key),
+                "extend_from_unique_slice received a duplicate key"
+            );
+            self.push_insert(key, value);
         }
     }
 
-    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
-        for i in (0..self.len()).rev() {
-            if !f(&self.keys[i], &mut self.values[i]) {
-                self.keys.swap_remove(i);
-                self.values.swap_remove(i);
+    /// Builds a map from `iter`, failing on the first pair whose key was already yielded.
+    ///
+    /// Unlike `FromIterator`, which lets the last value win, the offending pair is returned as is.
+    pub fn try_from_iter_strict<I: IntoIterator<Item = (K, V)>>(iter: I) -> Result<Self, (K, V)>
+    where
+        K: PartialEq,
+    {
+        let iterator = iter.into_iter();
+        let mut this = Self::with_capacity(iterator.size_hint().0);
+        for (key, value) in iterator {
+            if this.contains_key(&key) {
+                return Err((key, value));
             }
+            unsafe { this.push_insert(key, value) };
         }
+        Ok(this)
     }
 
-    pub fn iter(&self) -> Iter<K, V> {
-        Iter {
-            iter: self.keys.iter().zip(self.values.iter()),
-        }
+    /// Builds a map from a vector of pairs, failing on the first pair whose key appeared earlier in it.
+    ///
+    /// See [`try_from_iter_strict`](#method.try_from_iter_strict).
+    pub fn try_from_vec(inner: Vec<(K, V)>) -> Result<Self, (K, V)>
+    where
+        K: PartialEq,
+    {
+        Self::try_from_iter_strict(inner)
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
-        IterMut {
-            iter: self.keys.iter().zip(self.values.iter_mut()),
-        }
+    /// Removes entries whose keys are duplicates of an earlier entry's key, keeping the first occurrence.
+    ///
+    /// This is an O(n²) pass meant to restore a map whose invariants were broken through its unsafe API.
+    /// The relative order of the remaining entries is preserved.
+    pub fn dedup(&mut self)
+    where
+        K: PartialEq,
+    {
+        let keep: Vec<bool> = (0..self.len())
+            .map(|i| !self.keys[..i].contains(&self.keys[i]))
+            .collect();
+        self.retain_flagged(&keep);
     }
 
-    pub fn sort(&mut self)
+    /// Removes entries whose keys are duplicates of a later entry's key, keeping the last occurrence.
+    ///
+    /// This is an O(n²) pass meant to restore a map whose invariants were broken through its unsafe API.
+    /// The relative order of the remaining entries is preserved.
+    pub fn dedup_keep_last(&mut self)
     where
-        K: Ord,
+        K: PartialEq,
     {
-        let mut indices: Vec<usize> = (0..self.len()).collect();
-        indices.sort_unstable_by_key(|i| &self.keys[*i]);
-        reorder_vec(&mut self.keys, indices.iter().copied());
-        reorder_vec(&mut self.values, indices.iter().copied());
+        let keep: Vec<bool> = (0..self.len())
+            .map(|i| !self.keys[i + 1..].contains(&self.keys[i]))
+            .collect();
+        self.retain_flagged(&keep);
     }
 
-    /// Much faster than `self == other`, but will return false if the order of the data isn't identical.
-    /// # Safety
-    /// Note that for the order of data with two `VecMap`s to be identical, they must either have been both sorted,
-    /// or they must have undergone the insertion and removal of keys in the same order.
-    pub unsafe fn identical(&self, other: &Self) -> bool
+    /// Applies `f` to every key, then drops the entries whose new key collides with an earlier
+    /// entry's, as [`dedup`](#method.dedup) does. Returns how many entries were dropped.
+    ///
+    /// This is the safe way to mutate keys in bulk: the map's uniqueness invariant holds again once it returns.
+    pub fn mutate_keys<F: FnMut(&mut K)>(&mut self, f: F) -> usize
     where
         K: PartialEq,
-        V: PartialEq,
     {
-        self.keys == other.keys && self.values == other.values
+        self.keys.iter_mut().for_each(f);
+        let len = self.len();
+        self.dedup();
+        len - self.len()
     }
 
-    pub fn keys(&self) -> Keys<K, V> {
-        Keys {
-            iter: self.keys.iter(),
-            _phantom: Default::default(),
-        }
+    fn retain_flagged(&mut self, keep: &[bool]) {
+        retain_in_place(&mut self.keys, &mut self.values, |i, _, _| keep[i]);
     }
 
-    pub fn values(&self) -> Values<K, V> {
-        Values {
-            iter: self.values.iter(),
-            _phantom: Default::default(),
+    pub fn drain(&mut self) -> Drain<K, V> {
+        Drain {
+            iter: self.keys.drain(..).zip(self.values.drain(..)),
         }
     }
-}
 
-impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for VecMap<K, V> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_map().entries(self.iter()).finish()
+    /// Moves all entries into `dest`, overwriting the values of keys `dest` already holds.
+    ///
+    /// `self` is left empty, but keeps its allocation for reuse.
+    pub fn drain_into(&mut self, dest: &mut VecMap<K, V>)
+    where
+        K: PartialEq,
+    {
+        dest.extend(self.drain());
     }
-}
 
-fn reorder_vec<T>(vec: &mut Vec<T>, order: impl Iterator<Item = usize>) {
-    use std::mem::MaybeUninit;
-    let mut buffer: Vec<MaybeUninit<T>> = vec.iter().map(|_| MaybeUninit::uninit()).collect();
-    for (from, to) in order.enumerate() {
-        std::mem::swap(&mut vec[to], unsafe { &mut *(buffer[from].as_mut_ptr()) });
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
     }
-    for i in 0..vec.len() {
-        std::mem::swap(&mut vec[i], unsafe { &mut *(buffer[i].as_mut_ptr()) });
+
+    /// Reserves capacity for `expected_unique` more distinct keys, which is the relevant figure when
+    /// inserting from a stream with many duplicate keys, rather than its total length.
+    ///
+    /// Since this is only a capacity plan, a reservation that can't be satisfied (such as one that would
+    /// overflow `usize`) returns an error rather than panicking, like [`try_reserve`](#method.try_reserve).
+    pub fn reserve_distinct(&mut self, expected_unique: usize) -> Result<(), TryReserveError> {
+        self.try_reserve(expected_unique)
     }
-}
 
-impl<K: PartialEq, V: PartialEq> PartialEq for VecMap<K, V> {
-    fn eq(&self, other: &Self) -> bool {
-        if self.len() != other.len() {
-            return false;
+    /// Reserves capacity for the entries `iter` is about to yield, typically before extending the map with it.
+    ///
+    /// This reserves for the upper bound of `iter`'s size hint, falling back to its lower bound
+    /// for iterators that don't have one. As with [`reserve_distinct`](#method.reserve_distinct),
+    /// a reservation that can't be satisfied, such as one for an endless iterator, returns an error.
+    pub fn reserve_for<I: Iterator>(&mut self, iter: &I) -> Result<(), TryReserveError> {
+        let (lower, upper) = iter.size_hint();
+        self.reserve_distinct(upper.unwrap_or(lower))
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.keys.reserve_exact(additional);
+        self.values.reserve_exact(additional);
+    }
+
+    /// Tries to reserve capacity for at least `additional` more entries, returning an error instead of
+    /// aborting if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.keys.try_reserve(additional)?;
+        self.values.try_reserve(additional)
+    }
+
+    /// Tries to reserve capacity for exactly `additional` more entries, returning an error instead of
+    /// aborting if the allocation fails.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.keys.try_reserve_exact(additional)?;
+        self.values.try_reserve_exact(additional)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.keys.shrink_to_fit();
+        self.values.shrink_to_fit();
+    }
+
+    /// Shrinks the capacity of the map with a lower bound, keeping room for at least
+    /// `min_capacity` entries, and never less than `len`.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.keys.shrink_to(min_capacity);
+        self.values.shrink_to(min_capacity);
+    }
+
+    #[post(!self.contains_key(key) -> ret.is_none())]
+    #[post(self.contains_key(key) -> ret.is_some())]
+    pub fn get_key_value<'l, Q: Equivalent<K> + ?Sized>(&'l self, key: &Q) -> Option<(&'l K, &'l V)> {
+        self.position(key).map(|p| (&self.keys[p], &self.values[p]))
+    }
+
+    /// Removes `key` from the map, returning its value if it was present.
+    ///
+    /// This is O(1) once the key is found, as the last entry is moved into the removed one's place.
+    /// Use [`shift_remove`](#method.shift_remove) to keep the other entries' order instead.
+    ///
+    /// With the `ordered` feature enabled, this behaves like `shift_remove`, as do all removals
+    /// of a single entry, so that the map's order always is the insertion order.
+    #[post(!old(self.contains_key(key)) -> ret.is_none())]
+    #[post(old(self.contains_key(key)) -> ret.is_some())]
+    #[post(self.contains_key(key) == false)]
+    pub fn remove<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<V> {
+        self.position(key).map(|index| self.remove_index(index).1)
+    }
+
+    /// Removes `key` from the map like [`remove`](#method.remove), also returning the key and the index
+    /// its entry had in the underlying vectors.
+    ///
+    /// The last entry is moved to that index, unless the removed entry was the last one, or the `ordered`
+    /// feature is enabled, in which case all the following entries move back by one.
+    pub fn remove_full<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<(usize, K, V)> {
+        let index = self.position(key)?;
+        let (key, value) = self.remove_index(index);
+        Some((index, key, value))
+    }
+
+    #[inline]
+    fn remove_index(&mut self, index: usize) -> (K, V) {
+        remove_at(&mut self.keys, &mut self.values, index)
+    }
+
+    /// Replaces the key `old` with `new`, keeping its value and its position in the map.
+    ///
+    /// Fails without modifying the map if `old` isn't in the map, or if `new` already is, unless
+    /// it designates the same entry as `old`.
+    pub fn rekey<Q: Equivalent<K> + ?Sized>(&mut self, old: &Q, new: K) -> Result<(), RekeyError>
+    where
+        K: PartialEq,
+    {
+        let index = self.position(old).ok_or(RekeyError::NotFound)?;
+        match self.position(&new) {
+            Some(other) if other != index => Err(RekeyError::AlreadyExists),
+            _ => {
+                self.keys[index] = new;
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes `key` from the map like [`remove`](#method.remove), then releases memory if the map
+    /// has become sparse, i.e. if fewer than a quarter of its capacity is in use.
+    ///
+    /// The capacity is then brought down to twice the length, so that alternating insertions and
+    /// removals don't reallocate every time.
+    pub fn remove_and_shrink<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<V> {
+        let removed = self.remove(key);
+        if removed.is_some() && self.len() < self.capacity() / 4 {
+            self.shrink_to(self.len() * 2);
+        }
+        removed
+    }
+
+    /// Removes `key` from the map, returning its value if it was present.
+    ///
+    /// Unlike [`remove`](#method.remove), this preserves the order of the remaining entries,
+    /// at the cost of shifting all the entries that followed the removed one.
+    #[post(!old(self.contains_key(key)) -> ret.is_none())]
+    #[post(old(self.contains_key(key)) -> ret.is_some())]
+    #[post(self.contains_key(key) == false)]
+    pub fn shift_remove<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<V> {
+        if let Some(index) = self.position(key) {
+            self.keys.remove(index);
+            Some(self.values.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<K, V>
+    where
+        K: PartialEq
+    {
+        match self
+            .keys()
+            .enumerate()
+            .find(|(_, k)|  &&key == k)
+            .map(|(n, _)| n) {
+                Some(index) => Entry::Occupied(OccupiedEntry{ map: self, index }),
+                None => Entry::Vacant(VacantEntry{ map: self, key }),
+            }
+    }
+
+    /// Like [`entry`](#method.entry), but takes the key by reference, only converting it into an owned key
+    /// if a value gets inserted in the vacant entry.
+    ///
+    /// ```
+    /// use vector_map::VecMap;
+    ///
+    /// let mut counts: VecMap<String, u32> = VecMap::new();
+    /// for word in "a b a".split(' ') {
+    ///     *counts.entry_ref(word).or_default() += 1;
+    /// }
+    /// assert_eq!(counts["a"], 2);
+    /// ```
+    pub fn entry_ref<'b, Q>(&mut self, key: &'b Q) -> EntryRef<'_, 'b, Q, K, V>
+    where
+        Q: Equivalent<K> + ToOwned<Owned = K> + ?Sized,
+    {
+        match self.position(key) {
+            Some(index) => EntryRef::Occupied(OccupiedEntry { map: self, index }),
+            None => EntryRef::Vacant(VacantEntryRef { map: self, key }),
+        }
+    }
+
+    /// Returns a mutable reference to the value stored for `key`, inserting the result of `default`
+    /// first if the key is absent. `default` is only called if the key is absent.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V
+    where
+        K: PartialEq,
+    {
+        let index = match self.position(&key) {
+            Some(index) => index,
+            None => {
+                self.keys.push(key);
+                self.values.push(default());
+                self.len() - 1
+            }
+        };
+        &mut self.values[index]
+    }
+
+    /// Returns a mutable reference to the value stored for `key`, inserting `V::default()` first if the key
+    /// is absent.
+    pub fn get_or_insert_default(&mut self, key: K) -> &mut V
+    where
+        K: PartialEq,
+        V: Default,
+    {
+        self.get_or_insert_with(key, V::default)
+    }
+
+    /// Returns a mutable reference to the value stored for `key`, inserting `default` first if `key`
+    /// is absent, along with whether that insertion happened.
+    ///
+    /// `default` is dropped if `key` was already present.
+    pub fn entry_or_insert(&mut self, key: K, default: V) -> (bool, &mut V)
+    where
+        K: PartialEq,
+    {
+        match self.position(&key) {
+            Some(index) => (false, &mut self.values[index]),
+            None => {
+                self.keys.push(key);
+                self.values.push(default);
+                (true, self.values.last_mut().unwrap())
+            }
+        }
+    }
+
+    /// Applies `modify` to the value stored for `key` if there is one, or inserts the result of `default`
+    /// otherwise, which makes accumulating into a map a one-liner.
+    ///
+    /// ```
+    /// use vector_map::VecMap;
+    ///
+    /// let mut counts = VecMap::new();
+    /// for word in "a b a c a".split(' ') {
+    ///     counts.update(word, || 1, |count| *count += 1);
+    /// }
+    /// assert_eq!(counts[&"a"], 3);
+    /// assert_eq!(counts[&"c"], 1);
+    /// ```
+    pub fn update<D: FnOnce() -> V, M: FnOnce(&mut V)>(&mut self, key: K, default: D, modify: M)
+    where
+        K: PartialEq,
+    {
+        match self.position(&key) {
+            Some(index) => modify(&mut self.values[index]),
+            None => {
+                self.keys.push(key);
+                self.values.push(default());
+            }
+        }
+    }
+
+    #[post(!old(self.contains_key(key)) -> ret.is_none())]
+    #[post(old(self.contains_key(key)) -> ret.is_some())]
+    #[post(self.contains_key(key) == false)]
+    pub fn remove_entry<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<(K, V)> {
+        self.position(key).map(|index| self.remove_index(index))
+    }
+
+    /// Removes all the given keys from the map in a single pass, returning how many entries were removed.
+    ///
+    /// Like [`retain`](#method.retain), this preserves the order of the remaining entries.
+    pub fn remove_all<I: IntoIterator<Item = K>>(&mut self, keys: I) -> usize
+    where
+        K: PartialEq,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let len = self.len();
+        self.retain(|k, _| !keys.contains(k));
+        len - self.len()
+    }
+
+    /// Returns an iterator that removes and yields the entries for which `pred` returns `true`.
+    ///
+    /// Entries are visited in order, and the remaining ones keep their order: they are compacted in a
+    /// single pass, so extracting from a map of n entries is O(n) however many entries match.
+    /// If the iterator is dropped before being fully consumed, the entries it didn't visit are kept.
+    /// If it is leaked instead, the map is left empty and its entries are leaked along with it.
+    pub fn extract_if<F: FnMut(&K, &mut V) -> bool>(&mut self, pred: F) -> ExtractIf<'_, K, V, F> {
+        ExtractIf::new(&mut self.keys, &mut self.values, pred)
+    }
+
+    /// Removes and returns an arbitrary entry from the map, in O(1).
+    #[post(ret.is_none() -> old(self.is_empty()))]
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        match (self.keys.pop(), self.values.pop()) {
+            (Some(key), Some(value)) => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    /// Removes and returns the first entry in the underlying vectors, shifting the others.
+    ///
+    /// This is O(n): to drain a whole map front to back, iterating over [`drain`](#method.drain) is cheaper.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((self.keys.remove(0), self.values.remove(0)))
+        }
+    }
+
+    /// Removes and returns the last entry in the underlying vectors, in O(1).
+    ///
+    /// This is the entry [`pop`](#method.pop) removes, named for use alongside [`pop_first`](#method.pop_first).
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.pop()
+    }
+
+    /// Retains only the entries for which `f` returns `true`, preserving the order of the retained entries.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|k, v| f(k, v))
+    }
+
+    /// Like [`retain`](#method.retain), but also passes each entry's index in the underlying vectors,
+    /// as it was before any entry got removed.
+    pub fn retain_indexed<F: FnMut(usize, &K, &mut V) -> bool>(&mut self, mut f: F) {
+        retain_in_place(&mut self.keys, &mut self.values, |i, k, v| f(i, k, v));
+    }
+
+    /// Like [`retain`](#method.retain), but also gives mutable access to the keys.
+    ///
+    /// Mutating a key so that it becomes equal to another retained key breaks the map's invariants,
+    /// just like [`push_insert`](#method.push_insert) would.
+    pub fn retain_mut<F: FnMut(&mut K, &mut V) -> bool>(&mut self, mut f: F) {
+        retain_in_place(&mut self.keys, &mut self.values, |_, k, v| f(k, v));
+    }
+
+    pub fn iter(&self) -> Iter<K, V> {
+        Iter {
+            iter: self.keys.iter().zip(self.values.iter()),
+        }
+    }
+
+    /// Iterates over clones of the entries, leaving the map untouched.
+    pub fn iter_cloned(&self) -> impl DoubleEndedIterator<Item = (K, V)> + ExactSizeIterator + '_
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.iter().map(|(k, v)| (k.clone(), v.clone()))
+    }
+
+    /// Clones the entries into a `HashMap`, leaving the map untouched.
+    #[cfg(feature = "std")]
+    pub fn to_hashmap(&self) -> std::collections::HashMap<K, V>
+    where
+        K: Eq + std::hash::Hash + Clone,
+        V: Clone,
+    {
+        self.iter_cloned().collect()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<K, V> {
+        IterMut {
+            iter: self.keys.iter().zip(self.values.iter_mut()),
+        }
+    }
+
+    /// Builds a new map with the same keys, and values obtained by applying `f` to this map's values.
+    pub fn map_values<W, F: FnMut(&V) -> W>(&self, f: F) -> VecMap<K, W>
+    where
+        K: Clone,
+    {
+        VecMap {
+            keys: self.keys.clone(),
+            values: self.values.iter().map(f).collect(),
+        }
+    }
+
+    /// Like [`map_values`](#method.map_values), but consumes the map to avoid cloning the keys.
+    pub fn into_map_values<W, F: FnMut(V) -> W>(self, f: F) -> VecMap<K, W> {
+        VecMap {
+            keys: self.keys,
+            values: self.values.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Returns the sum of the map's values.
+    pub fn sum_values(&self) -> V
+    where
+        V: core::iter::Sum + Copy,
+    {
+        self.values.iter().copied().sum()
+    }
+
+    /// Folds the map's values into an accumulator, in the map's order.
+    pub fn fold_values<B, F: FnMut(B, &V) -> B>(&self, init: B, f: F) -> B {
+        self.values.iter().fold(init, f)
+    }
+
+    /// Drops the entries past the first `len` ones in the underlying vectors.
+    ///
+    /// This has no effect if `len` is greater than the map's current length.
+    pub fn truncate(&mut self, len: usize) {
+        self.keys.truncate(len);
+        self.values.truncate(len);
+    }
+
+    /// Moves the entries from index `at` onwards in the underlying vectors into a new map.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        VecMap {
+            keys: self.keys.split_off(at),
+            values: self.values.split_off(at),
+        }
+    }
+
+    /// Moves the entries of `other` into the map, calling `resolve` with the key, the current value and
+    /// `other`'s value to compute the merged value for keys both maps contain.
+    ///
+    /// Merged entries keep their position. If `resolve` panics, the entry being merged is lost,
+    /// but the map remains valid.
+    ///
+    /// ```
+    /// use vector_map::VecMap;
+    ///
+    /// let mut a: VecMap<_, _> = vec![("x", 1), ("y", 2)].into_iter().collect();
+    /// let b: VecMap<_, _> = vec![("y", 10), ("z", 20)].into_iter().collect();
+    /// a.merge_with(b, |_, current, other| current + other);
+    /// assert!(a.iter().eq(vec![(&"x", &1), (&"y", &12), (&"z", &20)]));
+    /// ```
+    pub fn merge_with<F: FnMut(&K, V, V) -> V>(&mut self, other: VecMap<K, V>, mut resolve: F)
+    where
+        K: PartialEq,
+    {
+        for (key, value) in other {
+            match self.position(&key) {
+                Some(index) => {
+                    let key = self.keys.swap_remove(index);
+                    let current = self.values.swap_remove(index);
+                    let merged = resolve(&key, current, value);
+                    self.keys.push(key);
+                    self.values.push(merged);
+                    // Undoes the `swap_remove`s' reordering.
+                    let last = self.len() - 1;
+                    self.swap(index, last);
+                }
+                None => {
+                    self.keys.push(key);
+                    self.values.push(value);
+                }
+            }
+        }
+    }
+
+    /// Consumes the map, splitting it into the entries for which `f` returns `true` and the others.
+    ///
+    /// Both maps keep the entries' relative order. As keys were unique to begin with, entries are moved
+    /// without searching for duplicates.
+    pub fn partition<F: FnMut(&K, &V) -> bool>(self, mut f: F) -> (Self, Self)
+    where
+        K: PartialEq,
+    {
+        let mut matching = VecMap::new();
+        let mut others = VecMap::new();
+        for (key, value) in self {
+            let target = if f(&key, &value) {
+                &mut matching
+            } else {
+                &mut others
+            };
+            unsafe { target.push_insert(key, value) };
+        }
+        (matching, others)
+    }
+
+    /// Consumes the map, splitting it into maps of `chunk_size` entries, the last one holding
+    /// the remainder.
+    ///
+    /// Entries keep their relative order, and are moved without searching for duplicates.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn into_chunks(self, chunk_size: usize) -> impl Iterator<Item = VecMap<K, V>>
+    where
+        K: PartialEq,
+    {
+        assert!(chunk_size != 0, "chunk_size must be non-zero");
+        let mut entries = self.into_iter().peekable();
+        core::iter::from_fn(move || {
+            entries.peek()?;
+            let mut chunk = VecMap::with_capacity(chunk_size.min(entries.len()));
+            for (key, value) in entries.by_ref().take(chunk_size) {
+                unsafe { chunk.push_insert(key, value) };
+            }
+            Some(chunk)
+        })
+    }
+
+    /// Swaps the positions of the entries at indices `a` and `b` in the underlying vectors.
+    ///
+    /// # Panics
+    /// Panics if `a` or `b` are out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.keys.swap(a, b);
+        self.values.swap(a, b);
+    }
+
+    /// Swaps the positions of the entries for keys `a` and `b` in the underlying vectors.
+    ///
+    /// Returns `false`, leaving the map untouched, if either key is absent.
+    pub fn swap_by_key<Q: Equivalent<K> + ?Sized>(&mut self, a: &Q, b: &Q) -> bool {
+        match (self.position(a), self.position(b)) {
+            (Some(a), Some(b)) => {
+                self.swap(a, b);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the entry stored at the front of the underlying vectors.
+    ///
+    /// This reflects the position in storage. Without the `ordered` feature, it only matches insertion order
+    /// until an entry is removed; with it, only explicit reorderings, such as sorting, make them differ.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.keys.first().zip(self.values.first())
+    }
+
+    /// Like [`first`](#method.first), with a mutable reference to the value.
+    pub fn first_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.keys.first().zip(self.values.first_mut())
+    }
+
+    /// Returns the entry stored at the back of the underlying vectors.
+    ///
+    /// This reflects the position in storage. Without the `ordered` feature, it only matches insertion order
+    /// until an entry is removed; with it, only explicit reorderings, such as sorting, make them differ.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.keys.last().zip(self.values.last())
+    }
+
+    /// Like [`last`](#method.last), with a mutable reference to the value.
+    pub fn last_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.keys.last().zip(self.values.last_mut())
+    }
+
+    /// Reorders the underlying vectors by key, in place.
+    ///
+    /// Since keys are unique, the unstable sort used here gives the same result a stable one would.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        let indices = self.sorted_indices();
+        self.reorder(&indices);
+    }
+
+    /// Reorders the underlying vectors by key, like [`sort_keys`](#method.sort_keys).
+    pub fn sort(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_keys();
+    }
+
+    /// Reorders the underlying vectors according to `compare`.
+    ///
+    /// This uses a stable sort, so entries that `compare` considers equal keep their relative order.
+    pub fn sort_by<F: FnMut((&K, &V), (&K, &V)) -> core::cmp::Ordering>(&mut self, mut compare: F) {
+        let mut indices: Vec<usize> = (0..self.len()).collect();
+        indices.sort_by(|&a, &b| {
+            compare(
+                (&self.keys[a], &self.values[a]),
+                (&self.keys[b], &self.values[b]),
+            )
+        });
+        self.reorder(&indices);
+    }
+
+    fn reorder(&mut self, indices: &[usize]) {
+        reorder_vec(&mut self.keys, indices.iter().copied());
+        reorder_vec(&mut self.values, indices.iter().copied());
+    }
+
+    /// Iterates over the entries in key order, without reordering the map itself.
+    ///
+    /// This sorts an index array on each call, and is mostly meant for debugging or deterministic output.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&K, &V)>
+    where
+        K: Ord,
+    {
+        self.sorted_indices()
+            .into_iter()
+            .map(move |i| (&self.keys[i], &self.values[i]))
+    }
+
+    /// Consumes the map, yielding its entries in key order.
+    ///
+    /// Unlike [`iter_sorted`](#method.iter_sorted), this doesn't build an index array: the entries
+    /// are sorted as pairs, then split back into the vectors the iterator walks.
+    pub fn into_iter_sorted(self) -> IntoIter<K, V>
+    where
+        K: Ord,
+    {
+        let mut entries: Vec<(K, V)> = self.keys.into_iter().zip(self.values).collect();
+        // Keys are unique, so an unstable sort yields the same order as a stable one.
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let (keys, values): (Vec<K>, Vec<V>) = entries.into_iter().unzip();
+        IntoIter {
+            iter: keys.into_iter().zip(values),
+        }
+    }
+
+    /// Iterates over the keys in order, without reordering the map itself.
+    pub fn keys_sorted(&self) -> impl Iterator<Item = &K>
+    where
+        K: Ord,
+    {
+        self.sorted_indices()
+            .into_iter()
+            .map(move |i| &self.keys[i])
+    }
+
+    /// Checks equality like `==` does, but in O(n log n) by comparing both maps in key order,
+    /// where `==` is O(n²) in the worst case.
+    ///
+    /// This pays off for large maps, whose entries may have been inserted in different orders.
+    pub fn eq_sorted(&self, other: &Self) -> bool
+    where
+        K: Ord,
+        V: PartialEq,
+    {
+        self.len() == other.len() && self.iter_sorted().eq(other.iter_sorted())
+    }
+
+    fn sorted_indices(&self) -> Vec<usize>
+    where
+        K: Ord,
+    {
+        let mut indices: Vec<usize> = (0..self.len()).collect();
+        indices.sort_unstable_by_key(|i| &self.keys[*i]);
+        indices
+    }
+
+    /// Returns a view of the map that looks keys up by binary search, in O(log n).
+    ///
+    /// The map must already be sorted by key, e.g. through [`sort_keys`](#method.sort_keys): this is only checked in
+    /// debug builds, and lookups through the view may miss existing keys otherwise.
+    pub fn as_sorted(&self) -> SortedView<'_, K, V>
+    where
+        K: Ord,
+    {
+        debug_assert!(
+            self.keys.windows(2).all(|w| w[0] < w[1]),
+            "as_sorted called on a map that isn't sorted by key"
+        );
+        SortedView { map: self }
+    }
+
+    /// Sorts the map by key and makes it read-only, so that lookups can use binary search.
+    ///
+    /// This pays off when a map is built once and then queried many times.
+    /// [`FrozenVecMap::thaw`](struct.FrozenVecMap.html#method.thaw) gives the map back.
+    pub fn freeze(mut self) -> FrozenVecMap<K, V>
+    where
+        K: Ord,
+    {
+        self.sort_keys();
+        FrozenVecMap { map: self }
+    }
+
+    /// Much faster than `self == other`, but will return false if the order of the data isn't identical.
+    /// # Safety
+    /// Note that for the order of data with two `VecMap`s to be identical, they must either have been both sorted,
+    /// or they must have undergone the insertion and removal of keys in the same order.
+    pub unsafe fn identical(&self, other: &Self) -> bool
+    where
+        K: PartialEq,
+        V: PartialEq,
+    {
+        self.keys == other.keys && self.values == other.values
+    }
+
+    pub fn keys(&self) -> Keys<K, V> {
+        Keys {
+            iter: self.keys.iter(),
+            _phantom: Default::default(),
+        }
+    }
+
+    pub fn values(&self) -> Values<K, V> {
+        Values {
+            iter: self.values.iter(),
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Returns the keys as a slice. The value of the key at index `i` is at index `i` of
+    /// [`values_as_slice`](#method.values_as_slice).
+    ///
+    /// Keys and values are stored separately, so the map can't be borrowed as a slice of pairs.
+    pub fn keys_as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the values as a slice, in the same order as [`keys_as_slice`](#method.keys_as_slice).
+    pub fn values_as_slice(&self) -> &[V] {
+        &self.values
+    }
+
+    /// Returns the values as a mutable slice, in the same order as [`keys_as_slice`](#method.keys_as_slice).
+    ///
+    /// This is safe, as the keys, and therefore their uniqueness, stay out of reach.
+    pub fn values_as_mut_slice(&mut self) -> &mut [V] {
+        &mut self.values
+    }
+}
+
+impl<K, V> VecMap<K, Vec<V>> {
+    /// Groups the values of `iter` by key, in the order they are encountered.
+    ///
+    /// ```
+    /// use vector_map::VecMap;
+    ///
+    /// let groups = VecMap::from_grouped(vec![("a", 1), ("b", 2), ("a", 3)]);
+    /// assert_eq!(groups[&"a"], vec![1, 3]);
+    /// assert_eq!(groups[&"b"], vec![2]);
+    /// ```
+    pub fn from_grouped<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self
+    where
+        K: PartialEq,
+    {
+        let mut groups = VecMap::new();
+        for (key, value) in iter {
+            groups.get_or_insert_with(key, Vec::new).push(value);
+        }
+        groups
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for VecMap<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+fn reorder_vec<T>(vec: &mut Vec<T>, order: impl Iterator<Item = usize>) {
+    use core::mem::MaybeUninit;
+    let mut buffer: Vec<MaybeUninit<T>> = vec.iter().map(|_| MaybeUninit::uninit()).collect();
+    for (from, to) in order.enumerate() {
+        core::mem::swap(&mut vec[to], unsafe { &mut *(buffer[from].as_mut_ptr()) });
+    }
+    for i in 0..vec.len() {
+        core::mem::swap(&mut vec[i], unsafe { &mut *(buffer[i].as_mut_ptr()) });
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for VecMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        for (key, value) in self.iter() {
+            match other.get(key) {
+                Some(v) if value == v => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> PartialEq<std::collections::HashMap<K, V, S>> for VecMap<K, V>
+where
+    K: Eq + std::hash::Hash,
+    V: PartialEq,
+    S: std::hash::BuildHasher,
+{
+    fn eq(&self, other: &std::collections::HashMap<K, V, S>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> PartialEq<VecMap<K, V>> for std::collections::HashMap<K, V, S>
+where
+    K: Eq + std::hash::Hash,
+    V: PartialEq,
+    S: std::hash::BuildHasher,
+{
+    fn eq(&self, other: &VecMap<K, V>) -> bool {
+        other == self
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq<alloc::collections::BTreeMap<K, V>> for VecMap<K, V> {
+    fn eq(&self, other: &alloc::collections::BTreeMap<K, V>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq<VecMap<K, V>> for alloc::collections::BTreeMap<K, V> {
+    fn eq(&self, other: &VecMap<K, V>) -> bool {
+        other == self
+    }
+}
+
+impl<'a, K: PartialEq + Copy + 'a, V: Copy + 'a> Extend<(&'a K, &'a V)> for VecMap<K, V> {
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(key, value)| (*key, *value)));
+    }
+}
+
+impl<K: PartialEq, V> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iterator = iter.into_iter();
+        self.reserve(iterator.size_hint().0);
+        for (key, value) in iterator {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: PartialEq, V> FromIterator<(K, V)> for VecMap<K, V> {
+    /// Builds a map by inserting the pairs in order: like with `HashMap`, when a key appears more than
+    /// once, the last value wins, while the first occurrence's key is the one kept.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let iterator = iter.into_iter();
+        let lower = iterator.size_hint().0;
+        let mut this = Self::with_capacity(lower);
+        this.extend(iterator);
+        this
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + std::hash::Hash, V, S> From<std::collections::HashMap<K, V, S>> for VecMap<K, V> {
+    fn from(map: std::collections::HashMap<K, V, S>) -> Self {
+        let mut this = Self::with_capacity(map.len());
+        for (key, value) in map {
+            // HashMap's keys are already unique.
+            unsafe { this.push_insert(key, value) };
+        }
+        this
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + std::hash::Hash, V, S: std::hash::BuildHasher + Default> From<VecMap<K, V>>
+    for std::collections::HashMap<K, V, S>
+{
+    fn from(map: VecMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// Keeps the `BTreeMap`'s ordering: the resulting map is sorted by key.
+impl<K, V> From<alloc::collections::BTreeMap<K, V>> for VecMap<K, V> {
+    fn from(map: alloc::collections::BTreeMap<K, V>) -> Self {
+        let (keys, values) = map.into_iter().unzip();
+        VecMap { keys, values }
+    }
+}
+
+impl<K: Ord, V> From<VecMap<K, V>> for alloc::collections::BTreeMap<K, V> {
+    fn from(map: VecMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<'a, Q: Equivalent<K> + ?Sized, K, V> Index<&'a Q> for VecMap<K, V> {
+    type Output = V;
+    fn index(&self, key: &'a Q) -> &Self::Output {
+        self.get(key).unwrap()
+    }
+}
+
+impl<'a, Q: Equivalent<K> + ?Sized, K, V> IndexMut<&'a Q> for VecMap<K, V> {
+    fn index_mut(&mut self, key: &'a Q) -> &mut Self::Output {
+        self.get_mut(key).unwrap()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut VecMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.keys.into_iter().zip(self.values.into_iter()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IntoIter<K, V> {
+    iter: core::iter::Zip<alloc::vec::IntoIter<K>, alloc::vec::IntoIter<V>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.iter.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+/// A view into a single occupied location in a `VecMap`.
+///
+/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
+pub struct OccupiedEntry<'a, K: 'a, V: 'a> {
+    map: &'a mut VecMap<K, V>,
+    index: usize,
+}
+
+/// A view into a single vacant location in a `VecMap`.
+///
+/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
+pub struct VacantEntry<'a, K: 'a, V: 'a> {
+    map: &'a mut VecMap<K, V>,
+    key: K,
+}
+
+/// A view into a single entry in a `VecMap`.
+///
+/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
+pub enum Entry<'a, K: 'a, V: 'a> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, K, V>),
+}
+use Entry::*;
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures that the entry is occupied by inserting the given value if it is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Occupied(entry) => entry.into_mut(),
+            Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures that the entry is occupied by inserting the the result of the given function if it
+    /// is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Occupied(entry) => entry.into_mut(),
+            Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Ensures that the entry is occupied by inserting the default value if it is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        &self.map.values[self.index]
+    }
+
+    /// Returns a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.values[self.index]
+    }
+
+    /// Returns a mutable reference to the entry's value with the same lifetime as the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.values[self.index]
+    }
+
+    /// Replaces the entry's value with the given one and returns the previous value.
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map and returns its value.
+    pub fn remove(self) -> V {
+        self.map.remove_index(self.index).1
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Inserts the entry into the map with the given value.
+    ///
+    /// Returns a mutable reference to the entry's value with the same lifetime as the map.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.keys.push(self.key);
+        self.map.values.push(value);
+        self.map.values.last_mut().unwrap()
+    }
+}
+
+/// A view into a vacant entry in a `VecMap`, holding a borrowed key.
+///
+/// See [`VecMap::entry_ref`](struct.VecMap.html#method.entry_ref) for details.
+pub struct VacantEntryRef<'a, 'b, Q: ?Sized, K: 'a, V: 'a> {
+    map: &'a mut VecMap<K, V>,
+    key: &'b Q,
+}
+
+/// A view into a single entry in a `VecMap`, whose key is only owned once inserted.
+///
+/// See [`VecMap::entry_ref`](struct.VecMap.html#method.entry_ref) for details.
+pub enum EntryRef<'a, 'b, Q: ?Sized, K: 'a, V: 'a> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+
+    /// A vacant entry.
+    Vacant(VacantEntryRef<'a, 'b, Q, K, V>),
+}
+
+impl<'a, 'b, Q: ToOwned<Owned = K> + ?Sized, K, V> EntryRef<'a, 'b, Q, K, V> {
+    /// Ensures that the entry is occupied by inserting the given value if it is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            EntryRef::Occupied(entry) => entry.into_mut(),
+            EntryRef::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures that the entry is occupied by inserting the the result of the given function if it
+    /// is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            EntryRef::Occupied(entry) => entry.into_mut(),
+            EntryRef::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Ensures that the entry is occupied by inserting the default value if it is vacant.
+    ///
+    /// Returns a mutable reference to the entry's value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, 'b, Q: ToOwned<Owned = K> + ?Sized, K, V> VacantEntryRef<'a, 'b, Q, K, V> {
+    /// Returns the borrowed key the entry was looked up with.
+    pub fn key(&self) -> &'b Q {
+        self.key
+    }
+
+    /// Inserts the entry into the map with an owned copy of the key and the given value.
+    ///
+    /// Returns a mutable reference to the entry's value with the same lifetime as the map.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.keys.push(self.key.to_owned());
+        self.map.values.push(value);
+        self.map.values.last_mut().unwrap()
+    }
+}
+
+/// A draining iterator over a `VecMap`.
+///
+/// See [`VecMap::drain`](struct.VecMap.html#method.drain) for details.
+pub struct Drain<'a, K: 'a, V: 'a> {
+    iter: core::iter::Zip<alloc::vec::Drain<'a, K>, alloc::vec::Drain<'a, V>>,
+}
+
+/// A read-only view of a key-sorted `VecMap`, using binary search for lookups.
+///
+/// See [`VecMap::as_sorted`](struct.VecMap.html#method.as_sorted) for details.
+pub struct SortedView<'a, K: 'a, V: 'a> {
+    map: &'a VecMap<K, V>,
+}
+
+impl<'a, K: Ord, V> SortedView<'a, K, V> {
+    #[inline]
+    fn position<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        self.map.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        self.position(key).is_some()
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&'a V>
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        let map = self.map;
+        self.position(key).map(|p| &map.values[p])
+    }
+
+    pub fn get_key_value<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&'a K, &'a V)>
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        let map = self.map;
+        self.position(key).map(|p| (&map.keys[p], &map.values[p]))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// A read-only `VecMap`, sorted by key for O(log n) lookups.
+///
+/// See [`VecMap::freeze`](struct.VecMap.html#method.freeze) for details.
+#[derive(Clone, Debug)]
+pub struct FrozenVecMap<K, V> {
+    map: VecMap<K, V>,
+}
+
+impl<K: Ord, V> FrozenVecMap<K, V> {
+    #[inline]
+    fn view(&self) -> SortedView<'_, K, V> {
+        SortedView { map: &self.map }
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        self.view().contains_key(key)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        self.view().get(key)
+    }
+
+    pub fn get_key_value<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: core::borrow::Borrow<Q>,
+    {
+        self.view().get_key_value(key)
+    }
+
+    /// Iterates over the entries in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Gives back the underlying map, still sorted by key.
+    pub fn thaw(self) -> VecMap<K, V> {
+        self.map
+    }
+}
+
+/// The error returned by [`VecMap::rekey`](struct.VecMap.html#method.rekey).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RekeyError {
+    /// The key to replace isn't in the map.
+    NotFound,
+    /// The replacement key is already in the map.
+    AlreadyExists,
+}
+
+impl core::fmt::Display for RekeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RekeyError::NotFound => write!(f, "the key to replace isn't in the map"),
+            RekeyError::AlreadyExists => write!(f, "the replacement key is already in the map"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RekeyError {}
+
+/// The error returned by [`VecMap::insert_at`](struct.VecMap.html#method.insert_at) when the
+/// requested index is past the end of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InsertAtError {
+    /// The requested index.
+    pub index: usize,
+    /// The map's length at the time of the insertion.
+    pub len: usize,
+}
+
+impl core::fmt::Display for InsertAtError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "insertion index (is {}) should be <= len (is {})",
+            self.index, self.len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InsertAtError {}
+
+/// An iterator removing the entries of a `VecMap` that match a predicate.
+///
+/// See [`VecMap::extract_if`](struct.VecMap.html#method.extract_if) for details.
+pub struct ExtractIf<'a, K: 'a, V: 'a, F> {
+    keys: &'a mut Vec<K>,
+    values: &'a mut Vec<V>,
+    /// The index of the next entry to visit.
+    read: usize,
+    /// The number of entries kept so far, which have all been moved to the front of the vectors.
+    write: usize,
+    /// The length of the vectors before the extraction started.
+    len: usize,
+    pred: F,
+}
+
+impl<'a, K, V, F: FnMut(&K, &mut V) -> bool> ExtractIf<'a, K, V, F> {
+    /// Starts extracting from a pair of vectors of the same length, which the other collections
+    /// of this crate also store their entries in.
+    pub(crate) fn new(keys: &'a mut Vec<K>, values: &'a mut Vec<V>, pred: F) -> Self {
+        let len = keys.len();
+        // The vectors' lengths are only restored by `ExtractIf`'s `Drop`, so that leaking it can't
+        // expose moved-out or duplicated entries.
+        unsafe {
+            keys.set_len(0);
+            values.set_len(0);
+        }
+        ExtractIf {
+            keys,
+            values,
+            read: 0,
+            write: 0,
+            len,
+            pred,
+        }
+    }
+}
+
+impl<'a, K, V, F: FnMut(&K, &mut V) -> bool> Iterator for ExtractIf<'a, K, V, F> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let keys = self.keys.as_mut_ptr();
+        let values = self.values.as_mut_ptr();
+        while self.read < self.len {
+            let i = self.read;
+            // Entries in `read..len` are untouched, and those in `write..read` have been moved out,
+            // either into the kept prefix or to the caller.
+            unsafe {
+                let extract = (self.pred)(&*keys.add(i), &mut *values.add(i));
+                self.read += 1;
+                if extract {
+                    return Some((core::ptr::read(keys.add(i)), core::ptr::read(values.add(i))));
+                }
+                if i != self.write {
+                    core::ptr::copy_nonoverlapping(keys.add(i), keys.add(self.write), 1);
+                    core::ptr::copy_nonoverlapping(values.add(i), values.add(self.write), 1);
+                }
+                self.write += 1;
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.read))
+    }
+}
+
+impl<'a, K, V, F> Drop for ExtractIf<'a, K, V, F> {
+    fn drop(&mut self) {
+        // Moves the unvisited entries, including one whose predicate panicked, after the kept ones.
+        let tail = self.len - self.read;
+        unsafe {
+            let keys = self.keys.as_mut_ptr();
+            let values = self.values.as_mut_ptr();
+            if self.read != self.write {
+                core::ptr::copy(keys.add(self.read), keys.add(self.write), tail);
+                core::ptr::copy(values.add(self.read), values.add(self.write), tail);
+            }
+            self.keys.set_len(self.write + tail);
+            self.values.set_len(self.write + tail);
+        }
+    }
+}
+
+/// An iterator yielding references to a `VecMap`'s keys and their corresponding values.
+///
+/// See [`VecMap::iter`](struct.VecMap.html#method.iter) for details.
+#[derive(Clone)]
+pub struct Iter<'a, K: 'a, V: 'a> {
+    iter: core::iter::Zip<core::slice::Iter<'a, K>, core::slice::Iter<'a, V>>,
+}
+
+/// An iterator yielding references to a `VecMap`'s keys and mutable references to their
+/// corresponding values.
+///
+/// See [`VecMap::iter_mut`](struct.VecMap.html#method.iter_mut) for details.
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    iter: core::iter::Zip<core::slice::Iter<'a, K>, core::slice::IterMut<'a, V>>,
+}
+
+/// An iterator yielding references to a `VecMap`'s keys in arbitrary order.
+///
+/// See [`VecMap::keys`](struct.VecMap.html#method.keys) for details.
+pub struct Keys<'a, K: 'a, V> {
+    iter: core::slice::Iter<'a, K>,
+    _phantom: core::marker::PhantomData<V>,
+}
+
+impl<'a, K, V> Clone for Keys<'a, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            iter: self.iter.clone(),
+            _phantom: Default::default(),
         }
-        for (key, value) in self.iter() {
-            match other.get(key) {
-                Some(v) if value == v => {}
-                _ => return false,
-            }
+    }
+}
+
+/// An iterator yielding references to a `VecMap`'s values in arbitrary order.
+///
+/// See [`VecMap::values`](struct.VecMap.html#method.values) for details.
+pub struct Values<'a, K, V: 'a> {
+    iter: core::slice::Iter<'a, V>,
+    _phantom: core::marker::PhantomData<K>,
+}
+
+impl<'a, K, V> Clone for Values<'a, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            iter: self.iter.clone(),
+            _phantom: Default::default(),
         }
-        true
     }
 }
 
-impl<'a, K: PartialEq + Copy + 'a, V: Copy + 'a> Extend<(&'a K, &'a V)> for VecMap<K, V> {
-    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
-        for (key, value) in iter.into_iter() {
-            self.insert(*key, *value);
+macro_rules! impl_iter {
+    ($typ:ty, $item:ty) => {
+        impl<'a, K, V> Iterator for $typ {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<'a, K, V> DoubleEndedIterator for $typ {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()
+            }
+        }
+
+        impl<'a, K, V> ExactSizeIterator for $typ {
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl<'a, K, V> FusedIterator for $typ {}
+    };
+}
+impl_iter! {Drain<'a,K,V>,  (K,V)}
+impl_iter! {Iter<'a,K,V>,  (&'a K, &'a V)}
+impl_iter! {IterMut<'a,K,V>,  (&'a K, &'a mut V)}
+impl_iter! {Keys<'a,K,V>,  &'a K}
+impl_iter! {Values<'a,K,V>,  &'a V}
+
+#[test]
+fn reorder() {
+    let n = 128;
+    let m = 128;
+    let expected: Vec<usize> = (0..n).collect();
+    let mut test = expected.clone();
+    for _ in 0..m {
+        let rands: Vec<usize> = test.iter().map(|_| rand::random()).collect();
+        test.sort_by_key(|x| rands[*x]);
+        let mut indices: Vec<usize> = (0..test.len()).collect();
+        indices.sort_unstable_by_key(|i| test[*i]);
+        reorder_vec(&mut test, indices.into_iter());
+        assert_eq!(test, expected);
+    }
+    for _ in 0..m {
+        let mut map: VecMap<usize, f32> = VecMap::with_capacity(n);
+        for _ in 0..n {
+            map.insert(rand::random(), rand::random());
         }
+        let clone = map.clone();
+        map.sort();
+        let mut map_iter = map.iter();
+        let first = *map_iter.by_ref().take(1).next().unwrap().0;
+        assert!(map_iter
+            .fold(Some(first), |acc, (k, _v)| {
+                let k = *k;
+                match acc {
+                    Some(v) if v < k => Some(k),
+                    _ => None,
+                }
+            })
+            .is_some());
+        assert_eq!(map, clone);
     }
 }
 
-impl<'a, K: PartialEq, V> Extend<(K, V)> for VecMap<K, V> {
-    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
-        for (key, value) in iter.into_iter() {
-            self.insert(key, value);
+#[test]
+fn unsized_key_queries() {
+    let mut map = VecMap::<String, u8>::new();
+    map.insert("foo".to_owned(), 1);
+    map.insert("bar".to_owned(), 2);
+
+    assert_eq!(&map["bar"], &2);
+}
+
+#[test]
+fn pop() {
+    let mut map: VecMap<u32, u32> = (0..16).map(|i| (i, i * 2)).collect();
+    let mut popped = Vec::new();
+    while let Some((k, v)) = map.pop() {
+        assert_eq!(v, k * 2);
+        popped.push(k);
+    }
+    assert!(map.is_empty());
+    popped.sort_unstable();
+    assert_eq!(popped, (0..16).collect::<Vec<_>>());
+    assert_eq!(map.pop(), None);
+}
+
+#[test]
+fn extend() {
+    let mut map = VecMap::new();
+    map.extend(vec![(1, "a"), (2, "b")]);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[&1], "a");
+
+    map.extend(vec![(2, "c"), (3, "d")]);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&2], "c");
+    assert_eq!(map[&3], "d");
+
+    let other: VecMap<_, _> = vec![(3, "e"), (4, "f")].into_iter().collect();
+    map.extend(other.iter());
+    assert_eq!(map.len(), 4);
+    assert_eq!(map[&3], "e");
+    assert_eq!(map[&4], "f");
+}
+
+#[test]
+fn capacity() {
+    let mut map: VecMap<u32, u32> = VecMap::new();
+    map.reserve(32);
+    assert!(map.capacity() >= 32);
+    map.reserve_exact(64);
+    assert!(map.capacity() >= 64);
+    map.insert(1, 1);
+    map.insert(2, 2);
+    map.shrink_to(16);
+    assert!(map.capacity() >= 16 && map.capacity() < 64);
+    map.shrink_to_fit();
+    assert!(map.capacity() >= 2 && map.capacity() < 16);
+    assert_eq!(map[&2], 2);
+}
+
+#[test]
+fn shrink_to_floor() {
+    let mut map: VecMap<u32, u32> = VecMap::with_capacity(128);
+    map.extend((0..10).map(|i| (i, i)));
+    map.shrink_to(32);
+    assert!(map.capacity() >= 32 && map.capacity() < 128);
+    map.shrink_to(4);
+    assert!(map.capacity() >= map.len());
+    assert!(map.iter().all(|(k, v)| k == v));
+
+    let mut set: crate::set::VecSet<u32> = crate::set::VecSet::with_capacity(128);
+    set.extend(0..10);
+    set.shrink_to(32);
+    assert!(set.capacity() >= 32 && set.capacity() < 128);
+    set.shrink_to(4);
+    assert!(set.capacity() >= set.len());
+    assert_eq!(set.len(), 10);
+}
+
+#[test]
+fn basic_queries() {
+    let mut map: VecMap<u32, u32> = VecMap::new();
+    assert!(map.is_empty());
+    assert!(!map.contains_key(&1));
+    map.insert(1, 10);
+    map.insert(2, 20);
+    map.insert(3, 30);
+    assert_eq!(map.len(), 3);
+    assert!(!map.is_empty());
+    assert!(map.contains_key(&2));
+    assert!(!map.contains_key(&4));
+
+    let mut drained: Vec<(u32, u32)> = map.drain().collect();
+    drained.sort_unstable();
+    assert_eq!(drained, vec![(1, 10), (2, 20), (3, 30)]);
+    assert!(map.is_empty());
+
+    map.insert(4, 40);
+    let capacity = map.capacity();
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.capacity(), capacity);
+}
+
+#[test]
+fn dedup() {
+    let build = || {
+        let mut map = VecMap::new();
+        for (k, v) in [(1, 'a'), (2, 'b'), (1, 'c'), (3, 'd'), (2, 'e'), (1, 'f')].iter() {
+            map.keys.push(*k);
+            map.values.push(*v);
         }
+        map
+    };
+
+    let mut map = build();
+    assert_eq!(map.len(), 6);
+    map.dedup();
+    assert_eq!(map.len(), 3);
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        vec![(&1, &'a'), (&2, &'b'), (&3, &'d')]
+    );
+
+    let mut map = build();
+    map.dedup_keep_last();
+    assert_eq!(map.len(), 3);
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        vec![(&3, &'d'), (&2, &'e'), (&1, &'f')]
+    );
+}
+
+#[test]
+fn get_or_insert_with() {
+    let mut map = VecMap::new();
+    map.insert("a", 1);
+
+    *map.get_or_insert_with("b", || 2) += 10;
+    assert_eq!(map[&"b"], 12);
+    assert_eq!(map.len(), 2);
+
+    let value = map.get_or_insert_with("a", || panic!("key is present"));
+    assert_eq!(*value, 1);
+    *value = 5;
+    assert_eq!(map[&"a"], 5);
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn from_iter_last_value_wins() {
+    let map: VecMap<_, _> = vec![(1, "a"), (1, "b")].into_iter().collect();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map[&1], "b");
+
+    let map: VecMap<_, _> = vec![(1, "a"), (2, "b"), (1, "c"), (2, "d"), (3, "e")]
+        .into_iter()
+        .collect();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&1], "c");
+    assert_eq!(map[&2], "d");
+    assert_eq!(map[&3], "e");
+}
+
+#[test]
+fn first_last() {
+    let mut map = VecMap::new();
+    assert_eq!(map.first(), None);
+    assert_eq!(map.last_mut(), None);
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+    assert_eq!(map.first(), Some((&"a", &1)));
+    assert_eq!(map.last(), Some((&"c", &3)));
+    *map.first_mut().unwrap().1 += 10;
+    *map.last_mut().unwrap().1 += 10;
+    assert_eq!(map[&"a"], 11);
+    assert_eq!(map[&"c"], 13);
+}
+
+#[test]
+fn iter_sorted() {
+    let pairs: Vec<(u32, u32)> = (0..64)
+        .map(|_| (rand::random::<u32>() % 128, rand::random()))
+        .collect();
+    let map: VecMap<_, _> = pairs.iter().copied().collect();
+    let btree: std::collections::BTreeMap<_, _> = pairs.iter().copied().collect();
+    let unsorted: Vec<_> = map.keys().copied().collect();
+    assert!(map.iter_sorted().eq(btree.iter()));
+    assert!(map.keys_sorted().eq(btree.keys()));
+    assert!(map.keys().copied().eq(unsorted));
+}
+
+#[test]
+fn sort_by() {
+    let mut map: VecMap<u32, i32> = vec![(3, 1), (1, 3), (4, 2), (2, 2)].into_iter().collect();
+    map.sort_by(|(_, a), (_, b)| a.cmp(b));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 4, 2, 1]);
+    map.sort_keys();
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    assert_eq!(map.get(&3), Some(&1));
+    map.sort_by(|(a, _), (b, _)| b.cmp(a));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+    assert_eq!(map[&1], 3);
+    assert_eq!(map[&4], 2);
+}
+
+#[test]
+fn removal_order() {
+    let build = || (0..6).map(|i| (i, i * 10)).collect::<VecMap<u32, u32>>();
+    let keys = |map: &VecMap<u32, u32>| map.keys().copied().collect::<Vec<_>>();
+
+    let mut map = build();
+    assert_eq!(map.remove(&1), Some(10));
+    if cfg!(feature = "ordered") {
+        assert_eq!(keys(&map), vec![0, 2, 3, 4, 5]);
+    } else {
+        assert_eq!(keys(&map), vec![0, 5, 2, 3, 4]);
     }
+
+    let mut map = build();
+    assert_eq!(map.shift_remove(&1), Some(10));
+    assert_eq!(map.shift_remove(&1), None);
+    assert_eq!(keys(&map), vec![0, 2, 3, 4, 5]);
+
+    let mut map = build();
+    map.retain(|k, v| {
+        *v += 1;
+        k % 2 == 1
+    });
+    assert_eq!(keys(&map), vec![1, 3, 5]);
+    assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![11, 31, 51]);
 }
 
-impl<K: PartialEq, V> FromIterator<(K, V)> for VecMap<K, V> {
-    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
-        let iterator = iter.into_iter();
-        let lower = iterator.size_hint().0;
-        let mut this = Self::with_capacity(lower);
-        this.extend(iterator);
-        this
+#[test]
+fn reserve_distinct() {
+    let mut map: VecMap<u32, u32> = (0..4).map(|i| (i, i)).collect();
+    assert!(map.reserve_distinct(16).is_ok());
+    assert!(map.capacity() >= map.len() + 16);
+    let capacity = map.capacity();
+    assert!(map.reserve_distinct(usize::MAX).is_err());
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.len(), 4);
+}
+
+#[test]
+fn hash_map_conversions() {
+    use std::collections::HashMap;
+    let hash_map: HashMap<u32, String> = (0..32).map(|i| (i, i.to_string())).collect();
+    let map = VecMap::from(hash_map.clone());
+    assert_eq!(map.len(), 32);
+    assert_eq!(map[&7], "7");
+    let back: HashMap<u32, String> = map.into();
+    assert_eq!(back, hash_map);
+}
+
+#[test]
+fn to_hashmap() {
+    let map: VecMap<u32, String> = (0..32).map(|i| (i, i.to_string())).collect();
+    let hash_map = map.to_hashmap();
+    assert_eq!(hash_map.len(), map.len());
+    assert_eq!(map, hash_map);
+    assert_eq!(hash_map[&7], "7");
+}
+
+#[test]
+fn btree_map_conversions() {
+    use std::collections::BTreeMap;
+    let btree_map: BTreeMap<u32, u32> = (0..32).map(|i| ((i * 7) % 32, i)).collect();
+    let map = VecMap::from(btree_map.clone());
+    assert!(map.keys().copied().eq(0..32));
+    assert!(map.iter().eq(btree_map.iter()));
+    let back: BTreeMap<u32, u32> = map.into();
+    assert_eq!(back, btree_map);
+}
+
+#[test]
+fn map_values() {
+    let map: VecMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    let strings = map.map_values(|v| v.to_string());
+    assert_eq!(strings.len(), 2);
+    assert_eq!(strings[&"a"], "1");
+    let doubled = map.into_map_values(|v| v * 2);
+    assert_eq!(doubled[&"a"], 2);
+    assert_eq!(doubled[&"b"], 4);
+}
+
+#[test]
+fn from_unique_iter() {
+    let map = unsafe { VecMap::from_unique_iter((0..64).map(|i| (i, i * 2))) };
+    assert_eq!(map.len(), 64);
+    assert_eq!(map[&10], 20);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn from_unique_iter_duplicate() {
+    unsafe { VecMap::from_unique_iter(vec![(1, 1), (1, 2)]) };
+}
+
+#[test]
+fn swap() {
+    let mut map: VecMap<u32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    map.swap(0, 2);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+    assert_eq!(map[&1], 'a');
+    assert!(map.swap_by_key(&3, &2));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3, 1]);
+    assert!(!map.swap_by_key(&3, &4));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 3, 1]);
+    assert_eq!(map[&3], 'c');
+}
+
+#[test]
+fn index_access() {
+    let mut map: VecMap<&str, u32> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    for key in ["a", "b", "c"].iter() {
+        let index = map.index_of(key).unwrap();
+        assert_eq!(map.get_index(index), Some((key, &map[key])));
     }
+    assert_eq!(map.index_of(&"d"), None);
+    assert_eq!(map.get_index(3), None);
+    *map.get_index_mut(1).unwrap().1 = 20;
+    assert_eq!(map[&"b"], 20);
+    assert!(map.get_index_mut(3).is_none());
 }
 
-impl<'a, Q: PartialEq<K> + ?Sized, K, V> Index<&'a Q> for VecMap<K, V> {
-    type Output = V;
-    fn index(&self, key: &'a Q) -> &Self::Output {
-        self.get(key).unwrap()
+#[test]
+fn truncate_split_off() {
+    let original: VecMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    let mut head = original.clone();
+    let tail = head.split_off(6);
+    assert_eq!(head.len(), 6);
+    assert_eq!(tail.len(), 4);
+    assert!(head.keys().all(|k| !tail.contains_key(k)));
+    let mut rejoined = head.clone();
+    rejoined.extend(tail);
+    assert_eq!(rejoined, original);
+
+    head.truncate(10);
+    assert_eq!(head.len(), 6);
+    head.truncate(2);
+    assert_eq!(head.keys().copied().collect::<Vec<_>>(), vec![0, 1]);
+}
+
+#[test]
+fn retain_mut() {
+    let mut map: VecMap<u32, u32> = (0..6).map(|i| (i, i)).collect();
+    map.retain_mut(|_, v| {
+        *v *= 10;
+        *v < 40
+    });
+    assert_eq!(
+        map.values().copied().collect::<Vec<_>>(),
+        vec![0, 10, 20, 30]
+    );
+
+    map.retain_mut(|k, _| {
+        *k += 100;
+        true
+    });
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec![100, 101, 102, 103]
+    );
+    assert_eq!(map[&102], 20);
+}
+
+#[test]
+fn remove_all() {
+    let mut map: VecMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    assert_eq!(map.remove_all(vec![]), 0);
+    assert_eq!(map.len(), 10);
+    let odd: set::VecSet<u32> = (0..20).filter(|i| i % 2 == 1).collect();
+    assert_eq!(map.remove_all(odd), 5);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![0, 2, 4, 6, 8]);
+    assert_eq!(map.remove_all(0..10), 5);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn has_unique_keys() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert!(map.has_unique_keys());
+    assert!(VecMap::<u32, u32>::new().has_unique_keys());
+    map.check_invariants();
+    map.keys.push(3);
+    map.values.push(3);
+    assert!(!map.has_unique_keys());
+    map.dedup();
+    assert!(map.has_unique_keys());
+    map.check_invariants();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "duplicate keys")]
+fn check_invariants() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    unsafe { map.push_insert(3, 3) };
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "duplicate keys")]
+fn insert_checks_invariants() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    map.keys.push(3);
+    map.values.push(3);
+    map.insert(8, 8);
+}
+
+#[test]
+fn duplicate_keys() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert!(map.duplicate_keys().is_empty());
+    for k in [3, 5, 3].iter() {
+        map.keys.push(*k);
+        map.values.push(0);
+    }
+    assert_eq!(map.duplicate_keys(), vec![&3, &5]);
+    map.dedup();
+    assert!(map.duplicate_keys().is_empty());
+}
+
+#[test]
+fn word_count() {
+    let text = "the cat and the dog and the bird";
+    let mut counts: VecMap<&str, u32> = VecMap::new();
+    for word in text.split(' ') {
+        *counts.get_or_insert_default(word) += 1;
+    }
+    assert_eq!(counts.len(), 5);
+    assert_eq!(counts[&"the"], 3);
+    assert_eq!(counts[&"and"], 2);
+    assert_eq!(counts[&"cat"], 1);
+
+    *counts.entry("the").or_default() += 1;
+    *counts.entry("fish").or_default() += 1;
+    assert_eq!(counts[&"the"], 4);
+    assert_eq!(counts[&"fish"], 1);
+}
+
+#[test]
+fn clone_from() {
+    let source: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    let mut scratch: VecMap<u32, u32> = VecMap::with_capacity(64);
+    let keys = scratch.keys.as_ptr();
+    scratch.clone_from(&source);
+    assert_eq!(scratch, source);
+    assert!(scratch.capacity() >= 64);
+    assert_eq!(scratch.keys.as_ptr(), keys);
+
+    let source: set::VecSet<u32> = (0..8).collect();
+    let mut scratch = set::VecSet::with_capacity(64);
+    scratch.clone_from(&source);
+    assert_eq!(scratch, source);
+    assert!(scratch.capacity() >= 64);
+}
+
+#[test]
+fn extract_if() {
+    let mut map: VecMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    let extracted: Vec<_> = map.extract_if(|k, _| k % 3 == 0).collect();
+    assert_eq!(extracted, vec![(0, 0), (3, 30), (6, 60), (9, 90)]);
+    assert_eq!(
+        map.keys().copied().collect::<Vec<_>>(),
+        vec![1, 2, 4, 5, 7, 8]
+    );
+
+    let mut iter = map.extract_if(|_, v| *v > 30);
+    assert_eq!(iter.next(), Some((4, 40)));
+    drop(iter);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 5, 7, 8]);
+}
+
+#[test]
+fn extract_if_drops() {
+    use std::rc::Rc;
+    let tracker = Rc::new(());
+    let mut map: VecMap<u32, Rc<()>> = (0..100).map(|i| (i, tracker.clone())).collect();
+    let extracted = map.extract_if(|k, _| k % 3 != 0).count();
+    assert_eq!(extracted, 66);
+    assert!(map.keys().copied().eq((0..100).step_by(3)));
+    assert_eq!(Rc::strong_count(&tracker), 35);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.extract_if(|k, _| if *k == 30 { panic!() } else { k % 2 == 0 })
+            .count()
+    }));
+    assert!(result.is_err());
+    assert!(map
+        .keys()
+        .copied()
+        .eq(vec![3, 9, 15, 21, 27, 30, 33, 36, 39]
+            .into_iter()
+            .chain((42..100).step_by(3))));
+    assert_eq!(Rc::strong_count(&tracker), map.len() + 1);
+
+    map.clear();
+    assert_eq!(Rc::strong_count(&tracker), 1);
+}
+
+#[test]
+fn retain_drops() {
+    use std::rc::Rc;
+    let tracker = Rc::new(());
+    let mut map: VecMap<u32, Rc<()>> = (0..100).map(|i| (i, tracker.clone())).collect();
+    let capacity = map.capacity();
+    map.retain(|k, _| k % 3 == 0);
+    assert!(map.keys().copied().eq((0..100).step_by(3)));
+    assert_eq!(Rc::strong_count(&tracker), 35);
+    assert_eq!(map.capacity(), capacity);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.retain(|k, _| if *k == 30 { panic!() } else { k % 2 == 0 })
+    }));
+    assert!(result.is_err());
+    assert_eq!(map.len(), 34);
+    assert!(map.has_unique_keys());
+    assert_eq!(Rc::strong_count(&tracker), 35);
+
+    #[cfg(feature = "smallvec")]
+    {
+        let mut small: small::SmallVecMap<u32, u32, 8> = (0..8).map(|i| (i, i)).collect();
+        small.retain(|k, _| k % 2 == 1);
+        assert!(small.keys().copied().eq(vec![1, 3, 5, 7]));
+        assert!(!small.spilled());
+    }
+}
+
+#[test]
+fn sorted_view() {
+    let mut map: VecMap<String, u32> = (0..64).rev().map(|i| (format!("{:02}", i), i)).collect();
+    map.sort_keys();
+    let sorted = map.as_sorted();
+    assert_eq!(sorted.len(), 64);
+    assert_eq!(sorted.get("42"), Some(&42));
+    assert_eq!(sorted.get_key_value("07"), Some((&"07".to_owned(), &7)));
+    assert!(!sorted.contains_key("64"));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn sorted_view_unsorted() {
+    let map: VecMap<u32, u32> = vec![(2, 2), (1, 1)].into_iter().collect();
+    map.as_sorted();
+}
+
+#[test]
+fn cross_type_equality() {
+    use std::collections::{BTreeMap, HashMap};
+    let map: VecMap<u32, u32> = (0..8).rev().map(|i| (i, i)).collect();
+    let hash_map: HashMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    let btree_map: BTreeMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map, hash_map);
+    assert_eq!(hash_map, map);
+    assert_eq!(map, btree_map);
+    assert_eq!(btree_map, map);
+
+    let shorter: HashMap<u32, u32> = (0..7).map(|i| (i, i)).collect();
+    assert_ne!(map, shorter);
+    let different: BTreeMap<u32, u32> = (0..8).map(|i| (i, i + 1)).collect();
+    assert_ne!(map, different);
+    assert_ne!(different, map);
+}
+
+#[test]
+fn insert_checked() {
+    let mut map: VecMap<u32, Option<u32>> = VecMap::new();
+    assert_eq!(map.insert_checked(1, None), (None, true));
+    assert_eq!(map.insert_checked(1, Some(2)), (Some(None), false));
+    assert_eq!(map.insert_checked(2, None), (None, true));
+    assert_eq!(map[&1], Some(2));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn insert_full() {
+    let mut map = VecMap::new();
+    assert_eq!(map.insert_full("a", 1), (0, None));
+    assert_eq!(map.insert_full("b", 2), (1, None));
+    let (index, previous) = map.insert_full("a", 3);
+    assert_eq!((index, previous), (0, Some(1)));
+    assert_eq!(map.get_index(index), Some((&"a", &3)));
+    assert_eq!(map.get_index(1), Some((&"b", &2)));
+}
+
+#[test]
+fn fused_iterators() {
+    fn fused<I: FusedIterator>(_: I) {}
+    let mut map: VecMap<u32, u32> = (0..4).map(|i| (i, i)).collect();
+    fused(map.iter());
+    fused(map.iter_mut());
+    fused(map.keys());
+    fused(map.values());
+    fused(map.drain());
+    fused(map.into_iter());
+
+    let a: set::VecSet<u32> = (0..4).collect();
+    let b: set::VecSet<u32> = (2..6).collect();
+    fused(a.iter());
+    fused(a.intersection(&b));
+    fused(a.difference(&b));
+    fused(a.union(&b));
+    fused(a.symmetric_difference(&b));
+    fused(a.clone().drain());
+    fused(a.into_iter());
+}
+
+#[test]
+fn strict_from_iter() {
+    let map = VecMap::try_from_iter_strict(vec![(1, "a"), (2, "b"), (3, "c")]).unwrap();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&2], "b");
+
+    let duplicate = VecMap::try_from_iter_strict(vec![(1, "a"), (2, "b"), (1, "c"), (2, "d")]);
+    assert_eq!(duplicate, Err((1, "c")));
+}
+
+#[test]
+fn remove_and_shrink() {
+    let mut map: VecMap<u32, u32> = (0..1024).map(|i| (i, i)).collect();
+    let peak = map.capacity();
+    assert!(peak >= 1024);
+    for i in 0..1000 {
+        assert_eq!(map.remove_and_shrink(&i), Some(i));
+    }
+    assert_eq!(map.len(), 24);
+    assert!(map.capacity() < peak / 4);
+    assert!(map.capacity() <= map.len() * 4);
+    assert_eq!(map.remove_and_shrink(&0), None);
+    assert_eq!(map.get(&1000), Some(&1000));
+}
+
+#[test]
+fn equivalent_lookups() {
+    let mut map: VecMap<String, u32> = VecMap::new();
+    map.insert("a".to_owned(), 1);
+    map.insert("b".to_owned(), 2);
+    // `str: PartialEq<String>`
+    assert_eq!(map.get("a"), Some(&1));
+    assert!(map.contains_key("b"));
+    // `String: Borrow<str>`
+    assert_eq!(map.get(&Borrowed("b")), Some(&2));
+    assert!(!map.contains_key(&Borrowed("c")));
+    assert_eq!(map.remove(&Borrowed("a")), Some(1));
+    assert_eq!(map.remove("a"), None);
+    assert_eq!(map.index_of(&Borrowed("b")), Some(0));
+    *map.get_mut(&Borrowed("b")).unwrap() += 1;
+    map[&Borrowed("b")] += 1;
+    assert_eq!(map[&Borrowed("b")], 4);
+    assert_eq!(
+        map.get_key_value(&Borrowed("b")),
+        Some((&"b".to_owned(), &4))
+    );
+    assert_eq!(map.remove_entry(&Borrowed("b")), Some(("b".to_owned(), 4)));
+
+    let mut map: VecMap<Box<[u8]>, u32> = VecMap::new();
+    map.insert(vec![1, 2].into_boxed_slice(), 12);
+    assert!(map.contains_key(&Borrowed(&[1, 2][..])));
+    assert!(!map.contains_key(&Borrowed(&[1][..])));
+    assert_eq!(map.remove(&Borrowed(&[1, 2][..])), Some(12));
+    assert!(map.is_empty());
+
+    let mut set: set::VecSet<Box<[u8]>> = vec![vec![1].into_boxed_slice()].into_iter().collect();
+    assert!(set.contains(&Borrowed(&[1][..])));
+    assert_eq!(set.get(&Borrowed(&[1][..])).map(|v| v.len()), Some(1));
+    assert!(set.remove(&Borrowed(&[1][..])));
+
+    let mut multi: multi::VecMultiMap<String, u32> = multi::VecMultiMap::new();
+    multi.insert("a".to_owned(), 1);
+    multi.insert("a".to_owned(), 2);
+    assert_eq!(multi.count(&Borrowed("a")), 2);
+    assert_eq!(multi.remove_all(&Borrowed("a")), vec![1, 2]);
+
+    #[cfg(feature = "smallvec")]
+    {
+        let mut small: small::SmallVecMap<String, u32, 2> = small::SmallVecMap::new();
+        small.insert("a".to_owned(), 1);
+        assert_eq!(small[&Borrowed("a")], 1);
+        assert_eq!(small.remove(&Borrowed("a")), Some(1));
     }
 }
 
-impl<'a, Q: PartialEq<K> + ?Sized, K, V> IndexMut<&'a Q> for VecMap<K, V> {
-    fn index_mut(&mut self, key: &'a Q) -> &mut Self::Output {
-        self.get_mut(key).unwrap()
+#[test]
+fn freeze_thaw() {
+    let map: VecMap<String, u32> = (0..64).rev().map(|i| (format!("{:02}", i), i)).collect();
+    let frozen = map.clone().freeze();
+    assert_eq!(frozen.len(), 64);
+    assert_eq!(frozen.get("42"), Some(&42));
+    assert_eq!(frozen.get_key_value("07"), Some((&"07".to_owned(), &7)));
+    assert!(!frozen.contains_key("64"));
+    assert!(frozen.iter().map(|(_, v)| *v).eq(0..64));
+
+    let thawed = frozen.thaw();
+    assert_eq!(thawed, map);
+    assert!(thawed.keys().zip(thawed.keys().skip(1)).all(|(a, b)| a < b));
+}
+
+#[test]
+fn eq_sorted() {
+    let map: VecMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let reversed: VecMap<u32, u32> = (0..100).rev().map(|i| (i, i)).collect();
+    assert!(map.eq_sorted(&reversed));
+    assert_eq!(map.eq_sorted(&reversed), map == reversed);
+
+    let mut different = reversed.clone();
+    different.insert(50, 0);
+    assert!(!map.eq_sorted(&different));
+    different.remove(&50);
+    assert!(!map.eq_sorted(&different));
+}
+
+#[test]
+fn slices() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map.keys_as_slice().len(), map.len());
+    for value in map.values_as_mut_slice() {
+        *value *= 2;
+    }
+    for (key, value) in map.keys_as_slice().iter().zip(map.values_as_slice()) {
+        assert_eq!(*value, key * 2);
+        assert_eq!(map[key], *value);
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
-    type Item = (&'a K, &'a V);
-    type IntoIter = Iter<'a, K, V>;
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
-    }
+#[test]
+fn update() {
+    let mut map: VecMap<u32, Vec<u32>> = VecMap::new();
+    map.update(1, || vec![1], |_| panic!("1 is absent"));
+    assert_eq!(map[&1], vec![1]);
+    map.update(1, || panic!("1 is present"), |v| v.push(2));
+    assert_eq!(map[&1], vec![1, 2]);
+    map.update(2, Vec::new, |v| v.push(3));
+    assert_eq!(map[&2], Vec::<u32>::new());
+    assert_eq!(map.len(), 2);
 }
 
-impl<'a, K, V> IntoIterator for &'a mut VecMap<K, V> {
-    type Item = (&'a K, &'a mut V);
-    type IntoIter = IterMut<'a, K, V>;
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
-    }
+#[test]
+fn entry_or_insert() {
+    let mut map: VecMap<&str, u32> = VecMap::new();
+    let (inserted, value) = map.entry_or_insert("a", 1);
+    assert!(inserted);
+    *value += 10;
+    assert_eq!(map[&"a"], 11);
+
+    let (inserted, value) = map.entry_or_insert("a", 100);
+    assert!(!inserted);
+    assert_eq!(*value, 11);
+    *value *= 2;
+    assert_eq!(map[&"a"], 22);
+    assert_eq!(map.len(), 1);
 }
 
-impl<K, V> IntoIterator for VecMap<K, V> {
-    type Item = (K, V);
-    type IntoIter = IntoIter<K, V>;
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter {
-            iter: self.keys.into_iter().zip(self.values.into_iter()),
-        }
-    }
+#[test]
+fn extend_from_slice() {
+    let mut map: VecMap<u32, u32> = vec![(1, 10), (2, 20)].into_iter().collect();
+    map.extend_from_slice(&[(2, 21), (3, 30), (3, 31)]);
+    assert_eq!(map.len(), 3);
+    assert!(map.keys().copied().eq(1..4));
+    assert_eq!(map[&2], 21);
+    assert_eq!(map[&3], 31);
+
+    unsafe { map.extend_from_unique_slice(&[(4, 40), (5, 50)]) };
+    assert_eq!(map.len(), 5);
+    assert_eq!(map[&5], 50);
+    assert!(map.has_unique_keys());
 }
 
-#[derive(Clone)]
-pub struct IntoIter<K, V> {
-    iter: std::iter::Zip<std::vec::IntoIter<K>, std::vec::IntoIter<V>>,
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn extend_from_unique_slice_with_duplicates() {
+    let mut map: VecMap<u32, u32> = vec![(1, 10)].into_iter().collect();
+    unsafe { map.extend_from_unique_slice(&[(2, 20), (1, 11)]) };
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
-    type Item = (K, V);
+#[test]
+fn iter_cloned() {
+    let map: VecMap<String, Vec<u32>> = (0..8).map(|i| (i.to_string(), vec![i])).collect();
+    let copy: VecMap<String, Vec<u32>> = map.iter_cloned().collect();
+    assert_eq!(copy, map);
+    assert_eq!(map.iter_cloned().len(), 8);
+    assert_eq!(
+        map.iter_cloned().next_back(),
+        Some(("7".to_owned(), vec![7]))
+    );
+}
 
-    fn next(&mut self) -> Option<(K, V)> {
-        self.iter.next()
-    }
+#[test]
+fn try_from_vec() {
+    let map = VecMap::try_from_vec(vec![("a", 1), ("b", 2)]).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[&"b"], 2);
+    assert!(map.capacity() >= 2);
+
+    let duplicate = VecMap::try_from_vec(vec![("a", 1), ("b", 2), ("b", 3)]);
+    assert_eq!(duplicate, Err(("b", 3)));
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
-    }
+#[test]
+fn get_pair_mut2() {
+    let mut map: VecMap<u32, u32> = (0..4).map(|i| (i, i * 10)).collect();
+    let (a, b) = map.get_pair_mut2(&3, &1).unwrap();
+    assert_eq!((*a, *b), (30, 10));
+    *a += 1;
+    *b += 2;
+    assert_eq!(map[&3], 31);
+    assert_eq!(map[&1], 12);
+
+    assert!(map.get_pair_mut2(&0, &4).is_none());
+    assert!(map.get_pair_mut2(&4, &0).is_none());
+    assert!(map.get_pair_mut2(&2, &2).is_none());
 }
 
-impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
-    fn next_back(&mut self) -> Option<(K, V)> {
-        self.iter.next_back()
-    }
+#[test]
+fn rekey() {
+    let mut map: VecMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    assert_eq!(map.rekey(&"a", "c"), Ok(()));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec!["c", "b"]);
+    assert_eq!(map[&"c"], 1);
+
+    assert_eq!(map.rekey(&"a", "d"), Err(RekeyError::NotFound));
+    assert_eq!(map.rekey(&"c", "b"), Err(RekeyError::AlreadyExists));
+    assert_eq!(map.rekey(&"c", "c"), Ok(()));
+    assert_eq!(map.len(), 2);
+    assert!(map.has_unique_keys());
 }
 
-impl<K, V> ExactSizeIterator for IntoIter<K, V> {
-    fn len(&self) -> usize {
-        self.iter.len()
-    }
+#[test]
+fn insert_at() {
+    let mut map: VecMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    assert_eq!(map.insert_at(1, "c", 3), Ok(None));
+    assert_eq!(map.insert_at(0, "d", 4), Ok(None));
+    assert_eq!(map.insert_at(4, "e", 5), Ok(None));
+    assert!(map.keys().copied().eq(vec!["d", "a", "c", "b", "e"]));
+
+    assert_eq!(map.insert_at(0, "b", 20), Ok(Some(2)));
+    assert_eq!(map.index_of(&"b"), Some(3));
+    assert_eq!(map[&"b"], 20);
+
+    assert_eq!(
+        map.insert_at(6, "f", 6),
+        Err(InsertAtError { index: 6, len: 5 })
+    );
+    assert_eq!(map.len(), 5);
+    assert!(!map.contains_key(&"f"));
 }
 
-/// A view into a single occupied location in a `VecMap`.
-///
-/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
-pub struct OccupiedEntry<'a, K: 'a, V: 'a> {
-    map: &'a mut VecMap<K, V>,
-    index: usize,
+#[test]
+fn aggregate_values() {
+    let map: VecMap<u32, u64> = (1..=10).map(|i| (i, i as u64)).collect();
+    assert_eq!(map.sum_values(), 55);
+    assert_eq!(VecMap::<u32, f32>::new().sum_values(), 0.0);
+
+    let map: VecMap<u32, &str> = vec![(1, "a"), (2, "b"), (3, "c")].into_iter().collect();
+    let joined = map.fold_values(String::new(), |mut acc, v| {
+        acc.push_str(v);
+        acc
+    });
+    assert_eq!(joined, "abc");
 }
 
-/// A view into a single vacant location in a `VecMap`.
-///
-/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
-pub struct VacantEntry<'a, K: 'a, V: 'a> {
-    map: &'a mut VecMap<K, V>,
-    key: K,
+#[test]
+fn clear_and_release() {
+    let mut map: VecMap<u32, u32> = (0..64).map(|i| (i, i)).collect();
+    let capacity = map.capacity();
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.capacity(), capacity);
+
+    map.extend((0..64).map(|i| (i, i)));
+    map.clear_and_release();
+    assert!(map.is_empty());
+    assert_eq!(map.capacity(), 0);
+    map.insert(1, 1);
+    assert_eq!(map[&1], 1);
 }
 
-/// A view into a single entry in a `VecMap`.
-///
-/// See [`VecMap::entry`](struct.VecMap.html#method.entry) for details.
-pub enum Entry<'a, K: 'a, V: 'a> {
-    /// An occupied entry.
-    Occupied(OccupiedEntry<'a, K, V>),
+#[test]
+fn drain_into() {
+    let mut scratch: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    let capacity = scratch.capacity();
+    let mut dest: VecMap<u32, u32> = (6..10).map(|i| (i, 0)).collect();
+    scratch.drain_into(&mut dest);
+    assert!(scratch.is_empty());
+    assert_eq!(scratch.capacity(), capacity);
+    assert_eq!(dest.len(), 10);
+    assert!((0..8).all(|i| dest[&i] == i));
+    assert_eq!(dest[&9], 0);
+}
 
-    /// A vacant entry.
-    Vacant(VacantEntry<'a, K, V>),
+#[test]
+fn mutate_keys() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map.mutate_keys(|k| *k += 10), 0);
+    assert!(map.keys().copied().eq(10..18));
+
+    assert_eq!(map.mutate_keys(|k| *k %= 3), 5);
+    assert_eq!(map.len(), 3);
+    assert!(map.has_unique_keys());
+    // The first entry to land on each key survives.
+    assert_eq!(map[&1], 0);
+    assert_eq!(map[&2], 1);
+    assert_eq!(map[&0], 2);
 }
-use Entry::*;
-impl<'a, K, V> Entry<'a, K, V> {
-    /// Ensures that the entry is occupied by inserting the given value if it is vacant.
-    ///
-    /// Returns a mutable reference to the entry's value.
-    pub fn or_insert(self, default: V) -> &'a mut V {
-        match self {
-            Occupied(entry) => entry.into_mut(),
-            Vacant(entry) => entry.insert(default),
-        }
-    }
 
-    /// Ensures that the entry is occupied by inserting the the result of the given function if it
-    /// is vacant.
-    ///
-    /// Returns a mutable reference to the entry's value.
-    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
-        match self {
-            Occupied(entry) => entry.into_mut(),
-            Vacant(entry) => entry.insert(default()),
-        }
+#[test]
+fn get_unchecked() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i * 2)).collect();
+    for index in 0..map.len() {
+        let checked = map.get_index(index).map(|(k, v)| (*k, *v));
+        assert_eq!(
+            Some(unsafe { map.get_unchecked(index) }).map(|(k, v)| (*k, *v)),
+            checked
+        );
+        let (_, value) = unsafe { map.get_unchecked_mut(index) };
+        *value += 1;
+        assert_eq!(
+            map.get_index(index).map(|(_, v)| *v),
+            checked.map(|(_, v)| v + 1)
+        );
     }
 }
 
-impl<'a, K, V> OccupiedEntry<'a, K, V> {
-    /// Returns a reference to the entry's value.
-    pub fn get(&self) -> &V {
-        &self.map.values[self.index]
-    }
+#[test]
+fn from_grouped() {
+    let groups = VecMap::from_grouped((0..10).map(|i| (i % 3, i)));
+    assert_eq!(groups.len(), 3);
+    assert!(groups.keys().copied().eq(0..3));
+    assert_eq!(groups[&0], vec![0, 3, 6, 9]);
+    assert_eq!(groups[&1], vec![1, 4, 7]);
+    assert_eq!(groups[&2], vec![2, 5, 8]);
+    assert!(VecMap::<u32, Vec<u32>>::from_grouped(None).is_empty());
+}
 
-    /// Returns a mutable reference to the entry's value.
-    pub fn get_mut(&mut self) -> &mut V {
-        &mut self.map.values[self.index]
-    }
+#[test]
+fn exact_size_iterators() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map.iter().len(), 8);
+    assert_eq!(map.iter().size_hint(), (8, Some(8)));
+    assert_eq!(map.iter_mut().len(), 8);
+    assert_eq!(map.keys().len(), 8);
+    assert_eq!(map.values().len(), 8);
+    let mut iter = map.iter();
+    iter.next();
+    iter.next_back();
+    assert_eq!(iter.len(), 6);
+
+    let keys: Vec<&u32> = map.keys().collect();
+    assert_eq!(keys.capacity(), 8);
+    assert_eq!(map.drain().len(), 8);
+    assert!(map.is_empty());
+}
 
-    /// Returns a mutable reference to the entry's value with the same lifetime as the map.
-    pub fn into_mut(self) -> &'a mut V {
-        &mut self.map.values[self.index]
-    }
+#[test]
+fn retain_indexed() {
+    let mut map: VecMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    map.retain_indexed(|i, _, v| {
+        *v += 1;
+        i % 2 == 0
+    });
+    assert!(map.keys().copied().eq((0..10).step_by(2)));
+    assert!(map.values().copied().eq((1..11).step_by(2)));
+
+    // Keeping only the first two entries matching a predicate.
+    let mut kept = 0;
+    map.retain_indexed(|_, k, _| {
+        let keep = *k > 0 && kept < 2;
+        kept += keep as usize;
+        keep
+    });
+    assert!(map.keys().copied().eq(vec![2, 4]));
+}
 
-    /// Replaces the entry's value with the given one and returns the previous value.
-    pub fn insert(&mut self, value: V) -> V {
-        std::mem::replace(self.get_mut(), value)
-    }
+#[test]
+fn reverse_lookup() {
+    let map: VecMap<&str, u32> = vec![("a", 1), ("b", 2), ("c", 2)].into_iter().collect();
+    assert!(map.contains_value(&1));
+    assert!(!map.contains_value(&3));
+    assert_eq!(map.find_key(&1), Some(&"a"));
+    assert!(matches!(map.find_key(&2), Some(&"b") | Some(&"c")));
+    assert_eq!(map.find_key(&3), None);
+}
 
-    /// Removes the entry from the map and returns its value.
-    pub fn remove(self) -> V {
-        self.map.keys.swap_remove(self.index);
-        self.map.values.swap_remove(self.index)
+#[test]
+fn find() {
+    let mut map: VecMap<u32, u32> = (1..10).map(|i| (i, i * 10)).collect();
+    assert_eq!(map.find(|_, v| *v > 45), Some((&5, &50)));
+    assert_eq!(map.find(|k, _| k % 4 == 0), Some((&4, &40)));
+    assert_eq!(map.find(|_, v| *v > 100), None);
+
+    if let Some((_, value)) = map.find_mut(|k, _| k % 3 == 0) {
+        *value = 0;
     }
+    assert_eq!(map[&3], 0);
+    assert_eq!(map[&6], 60);
+    assert!(map.find_mut(|k, _| *k == 10).is_none());
 }
 
-impl<'a, K, V> VacantEntry<'a, K, V> {
-    /// Inserts the entry into the map with the given value.
-    ///
-    /// Returns a mutable reference to the entry's value with the same lifetime as the map.
-    pub fn insert(self, value: V) -> &'a mut V {
-        self.map.keys.push(self.key);
-        self.map.values.push(value);
-        self.map.values.last_mut().unwrap()
-    }
+#[test]
+fn count() {
+    let map: VecMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    assert_eq!(map.count(|k, _| k % 2 == 0), 5);
+    assert_eq!(map.count(|_, v| (20..50).contains(v)), 3);
+    assert_eq!(map.count(|_, _| false), 0);
+    assert_eq!(VecMap::<u32, u32>::new().count(|_, _| true), 0);
 }
 
-/// A draining iterator over a `VecMap`.
-///
-/// See [`VecMap::drain`](struct.VecMap.html#method.drain) for details.
-pub struct Drain<'a, K: 'a, V: 'a> {
-    iter: std::iter::Zip<std::vec::Drain<'a, K>, std::vec::Drain<'a, V>>,
+#[test]
+fn get_many() {
+    let map: VecMap<u32, u32> = (0..4).map(|i| (i, i * 10)).collect();
+    assert_eq!(
+        map.get_many(&[3, 7, 0, 3]),
+        vec![Some(&30), None, Some(&0), Some(&30)]
+    );
+    assert!(map.get_many::<u32>(&[]).is_empty());
 }
 
-/// An iterator yielding references to a `VecMap`'s keys and their corresponding values.
-///
-/// See [`VecMap::iter`](struct.VecMap.html#method.iter) for details.
-#[derive(Clone)]
-pub struct Iter<'a, K: 'a, V: 'a> {
-    iter: std::iter::Zip<std::slice::Iter<'a, K>, std::slice::Iter<'a, V>>,
+#[test]
+fn into_iter_sorted() {
+    use alloc::collections::BTreeMap;
+    let pairs: Vec<(u32, u32)> = (0..32).map(|i| ((i * 7) % 32, i)).collect();
+    let map: VecMap<u32, u32> = pairs.iter().copied().collect();
+    let btree_map: BTreeMap<u32, u32> = pairs.into_iter().collect();
+    assert!(map.into_iter_sorted().eq(btree_map));
+
+    let strings: VecMap<String, u32> = (0..10).rev().map(|i| (i.to_string(), i)).collect();
+    assert!(strings.into_iter_sorted().map(|(_, v)| v).eq(0..10));
 }
 
-/// An iterator yielding references to a `VecMap`'s keys and mutable references to their
-/// corresponding values.
-///
-/// See [`VecMap::iter_mut`](struct.VecMap.html#method.iter_mut) for details.
-pub struct IterMut<'a, K: 'a, V: 'a> {
-    iter: std::iter::Zip<std::slice::Iter<'a, K>, std::slice::IterMut<'a, V>>,
+#[test]
+fn reserve_for() {
+    let mut map: VecMap<u32, u32> = VecMap::new();
+    let pairs = (0..100).map(|i| (i, i));
+    assert!(map.reserve_for(&pairs).is_ok());
+    assert!(map.capacity() >= 100);
+    map.extend(pairs);
+
+    let evens = (100..200).filter(|i| i % 2 == 0).map(|i| (i, i));
+    assert!(map.reserve_for(&evens).is_ok());
+    assert!(map.capacity() >= 200);
+    map.extend(evens);
+    assert_eq!(map.len(), 150);
+
+    let mut map: VecMap<u32, u32> = VecMap::new();
+    assert!(map.reserve_for(&(0..).map(|i| (i, i))).is_err());
+    assert_eq!(map.capacity(), 0);
 }
 
-/// An iterator yielding references to a `VecMap`'s keys in arbitrary order.
-///
-/// See [`VecMap::keys`](struct.VecMap.html#method.keys) for details.
-pub struct Keys<'a, K: 'a, V> {
-    iter: std::slice::Iter<'a, K>,
-    _phantom: std::marker::PhantomData<V>,
+#[test]
+fn get_or() {
+    let map: VecMap<&str, String> = vec![("host", "localhost".to_owned())].into_iter().collect();
+    let fallback = "-".to_owned();
+    assert_eq!(map.get_or(&"host", &fallback), "localhost");
+    assert_eq!(map.get_or(&"port", &fallback), "-");
+    assert_eq!(map.get_or_else(&"host", || unreachable!()), "localhost");
+    assert_eq!(map.get_or_else(&"port", || &fallback), "-");
 }
 
-impl<'a, K, V> Clone for Keys<'a, K, V> {
-    fn clone(&self) -> Self {
-        Keys {
-            iter: self.iter.clone(),
-            _phantom: Default::default(),
-        }
+#[test]
+fn partition() {
+    let map: VecMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    let (even, odd) = map.clone().partition(|k, _| k % 2 == 0);
+    assert_eq!(even.len() + odd.len(), map.len());
+    assert!(even.keys().copied().eq((0..10).step_by(2)));
+    assert!(odd.keys().copied().eq((1..10).step_by(2)));
+    for (key, value) in &map {
+        assert_ne!(even.get(key) == Some(value), odd.get(key) == Some(value));
     }
 }
 
-/// An iterator yielding references to a `VecMap`'s values in arbitrary order.
-///
-/// See [`VecMap::values`](struct.VecMap.html#method.values) for details.
-pub struct Values<'a, K, V: 'a> {
-    iter: std::slice::Iter<'a, V>,
-    _phantom: std::marker::PhantomData<K>,
+#[test]
+fn into_chunks() {
+    let map: VecMap<u32, u32> = (0..9).map(|i| (i, i * 10)).collect();
+    let chunks: Vec<_> = map.clone().into_chunks(3).collect();
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|chunk| chunk.len() == 3));
+    assert!(chunks.iter().flatten().map(|(k, _)| *k).eq(0..9));
+
+    let chunks: Vec<_> = map.clone().into_chunks(4).collect();
+    assert_eq!(
+        chunks.iter().map(VecMap::len).collect::<Vec<_>>(),
+        vec![4, 4, 1]
+    );
+    assert_eq!(chunks[2][&8], 80);
+
+    assert_eq!(map.into_chunks(20).count(), 1);
+    assert_eq!(VecMap::<u32, u32>::new().into_chunks(3).count(), 0);
 }
 
-impl<'a, K, V> Clone for Values<'a, K, V> {
-    fn clone(&self) -> Self {
-        Values {
-            iter: self.iter.clone(),
-            _phantom: Default::default(),
-        }
-    }
+#[test]
+#[should_panic]
+fn into_chunks_of_zero() {
+    let map: VecMap<u32, u32> = (0..9).map(|i| (i, i)).collect();
+    let _ = map.into_chunks(0);
 }
 
-macro_rules! impl_iter {
-    ($typ:ty, $item:ty) => {
-        impl<'a, K, V> Iterator for $typ {
-            type Item = $item;
-
-            fn next(&mut self) -> Option<Self::Item> {
-                self.iter.next()
-            }
+#[test]
+fn merge_with() {
+    let mut map: VecMap<u32, u32> = (0..6).map(|i| (i, i)).collect();
+    map.merge_with((4..8).map(|i| (i, 100)).collect(), |_, a, b| a + b);
+    assert!(map.keys().copied().eq(0..8));
+    assert!(map
+        .values()
+        .copied()
+        .eq(vec![0, 1, 2, 3, 104, 105, 100, 100]));
+
+    let mut disjoint: VecMap<u32, u32> = (0..3).map(|i| (i, i)).collect();
+    disjoint.merge_with((3..6).map(|i| (i, i)).collect(), |_, _, _| unreachable!());
+    assert_eq!(disjoint, (0..6).map(|i| (i, i)).collect::<VecMap<_, _>>());
+}
 
-            fn size_hint(&self) -> (usize, Option<usize>) {
-                self.iter.size_hint()
-            }
-        }
+#[test]
+#[cfg(feature = "ordered")]
+fn ordered_removals() {
+    let mut map: VecMap<u32, u32> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map.remove(&1), Some(1));
+    assert_eq!(map.remove_entry(&4), Some((4, 4)));
+    if let Entry::Occupied(entry) = map.entry(0) {
+        assert_eq!(entry.remove(), 0);
+    }
+    assert!(map.keys().copied().eq(vec![2, 3, 5, 6, 7]));
+    assert_eq!(map.remove_full(&3), Some((1, 3, 3)));
+    assert_eq!(map.pop_first(), Some((2, 2)));
+    assert!(map.keys().copied().eq(vec![5, 6, 7]));
 
-        impl<'a, K, V> DoubleEndedIterator for $typ {
-            fn next_back(&mut self) -> Option<Self::Item> {
-                self.iter.next_back()
-            }
-        }
+    let mut set: set::VecSet<u32> = (0..8).collect();
+    set.remove(&2);
+    assert!(set.iter().copied().eq(vec![0, 1, 3, 4, 5, 6, 7]));
 
-        impl<'a, K, V> ExactSizeIterator for $typ {
-            fn len(&self) -> usize {
-                self.iter.len()
-            }
-        }
-    };
+    #[cfg(feature = "smallvec")]
+    {
+        let mut small: small::SmallVecMap<u32, u32, 8> = (0..8).map(|i| (i, i)).collect();
+        assert_eq!(small.remove(&1), Some(1));
+        assert_eq!(small.remove_entry(&4), Some((4, 4)));
+        assert!(small.keys().copied().eq(vec![0, 2, 3, 5, 6, 7]));
+    }
 }
-impl_iter! {Drain<'a,K,V>,  (K,V)}
-impl_iter! {Iter<'a,K,V>,  (&'a K, &'a V)}
-impl_iter! {IterMut<'a,K,V>,  (&'a K, &'a mut V)}
-impl_iter! {Keys<'a,K,V>,  &'a K}
-impl_iter! {Values<'a,K,V>,  &'a V}
 
 #[test]
-fn reorder() {
-    let n = 128;
-    let m = 128;
-    let expected: Vec<usize> = (0..n).collect();
-    let mut test = expected.clone();
-    for _ in 0..m {
-        let rands: Vec<usize> = test.iter().map(|_| rand::random()).collect();
-        test.sort_by_key(|x| rands[*x]);
-        let mut indices: Vec<usize> = (0..test.len()).collect();
-        indices.sort_unstable_by_key(|i| test[*i]);
-        reorder_vec(&mut test, indices.into_iter());
-        assert_eq!(test, expected);
+fn pop_first_last() {
+    let mut map: VecMap<u32, u32> = (0..6).map(|i| (i, i * 10)).collect();
+    assert_eq!(map.pop_last(), Some((5, 50)));
+    let mut popped = Vec::new();
+    while let Some((key, _)) = map.pop_first() {
+        popped.push(key);
     }
-    for _ in 0..m {
-        let mut map: VecMap<usize, f32> = VecMap::with_capacity(n);
-        for _ in 0..n {
-            map.insert(rand::random(), rand::random());
+    assert_eq!(popped, vec![0, 1, 2, 3, 4]);
+    assert!(map.is_empty());
+    assert_eq!(map.pop_last(), None);
+}
+
+#[test]
+fn entry_ref() {
+    use core::cell::Cell;
+    std::thread_local!(static CLONES: Cell<usize> = Cell::new(0));
+
+    #[derive(Debug, PartialEq)]
+    struct Key(u32);
+    impl Clone for Key {
+        fn clone(&self) -> Self {
+            CLONES.with(|clones| clones.set(clones.get() + 1));
+            Key(self.0)
         }
-        let clone = map.clone();
-        map.sort();
-        let mut map_iter = map.iter();
-        let first = *map_iter.by_ref().take(1).next().unwrap().0;
-        assert!(map_iter
-            .fold(Some(first), |acc, (k, _v)| {
-                let k = *k;
-                match acc {
-                    Some(v) if v < k => Some(k),
-                    _ => None,
-                }
-            })
-            .is_some());
-        assert_eq!(map, clone);
     }
+
+    let mut map: VecMap<Key, u32> = VecMap::new();
+    *map.entry_ref(&Key(1)).or_insert(0) += 1;
+    assert_eq!(CLONES.with(Cell::get), 1);
+    *map.entry_ref(&Key(1)).or_insert(0) += 1;
+    *map.entry_ref(&Key(1)).or_insert_with(|| unreachable!()) += 1;
+    assert_eq!(CLONES.with(Cell::get), 1);
+    assert_eq!(map[&Key(1)], 3);
+
+    match map.entry_ref(&Key(2)) {
+        EntryRef::Vacant(entry) => assert_eq!(entry.key(), &Key(2)),
+        EntryRef::Occupied(_) => panic!("2 isn't in the map"),
+    }
+    assert_eq!(CLONES.with(Cell::get), 1);
 }
 
 #[test]
-fn unsized_key_queries() {
-    let mut map = VecMap::<String, u8>::new();
-    map.insert("foo".to_owned(), 1);
-    map.insert("bar".to_owned(), 2);
+fn remove_full() {
+    let mut map: VecMap<u32, u32> = (0..6).map(|i| (i, i * 10)).collect();
+    assert_eq!(map.remove_full(&2), Some((2, 2, 20)));
+    assert_eq!(map.remove_full(&2), None);
+    let expected = if cfg!(feature = "ordered") { 3 } else { 5 };
+    assert_eq!(map.get_index(2), Some((&expected, &(expected * 10))));
+
+    let last = map.len() - 1;
+    let last_key = *map.get_index(last).unwrap().0;
+    assert_eq!(map.remove_full(&last_key).map(|(i, _, _)| i), Some(last));
+    assert_eq!(map.len(), 4);
+}
 
-    assert_eq!(&map["bar"], &2);
+#[test]
+fn try_reserve() {
+    let mut map: VecMap<u32, u32> = VecMap::new();
+    assert!(map.try_reserve(16).is_ok());
+    assert!(map.capacity() >= 16);
+    assert!(map.try_reserve_exact(32).is_ok());
+    assert!(map.capacity() >= 32);
+    assert!(map.try_reserve(usize::MAX).is_err());
+    assert!(map.try_reserve_exact(usize::MAX).is_err());
 }
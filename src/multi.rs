@@ -0,0 +1,187 @@
+use crate::{retain_in_place, Equivalent, ExtractIf, Iter, IterMut, Keys, Values};
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+/// A `VecMap` variant that allows several values per key.
+///
+/// Inserting never overwrites: each value is appended, and the values of a key are
+/// visited in the order they were inserted, as long as no entry is removed in between.
+#[derive(Clone, Default)]
+pub struct VecMultiMap<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+}
+
+impl<K, V> VecMultiMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecMultiMap {
+            keys: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of entries, counting each value of a key separately.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity().min(self.values.capacity())
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    #[inline]
+    fn positions<'a, Q: Equivalent<K> + ?Sized>(
+        &'a self,
+        key: &'a Q,
+    ) -> impl DoubleEndedIterator<Item = usize> + 'a {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(move |(_, k)| key.equivalent(k))
+            .map(|(i, _)| i)
+    }
+
+    pub fn contains_key<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> bool {
+        self.keys.iter().any(|k| key.equivalent(k))
+    }
+
+    /// Returns the number of values stored for `key`.
+    pub fn count<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> usize {
+        self.keys.iter().filter(|k| key.equivalent(k)).count()
+    }
+
+    /// Returns the first value stored for `key`.
+    pub fn get<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> Option<&V> {
+        self.positions(key).next().map(|p| &self.values[p])
+    }
+
+    /// Iterates over all the values stored for `key`.
+    pub fn get_all<'a, Q: Equivalent<K> + ?Sized>(
+        &'a self,
+        key: &'a Q,
+    ) -> impl DoubleEndedIterator<Item = &'a V> + 'a {
+        self.positions(key).map(move |p| &self.values[p])
+    }
+
+    /// Appends a value for `key`, keeping any value already stored for it.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.keys.push(key);
+        self.values.push(value);
+    }
+
+    /// Removes all the values stored for `key`, returning them in order.
+    ///
+    /// The remaining entries keep their order.
+    pub fn remove_all<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Vec<V> {
+        if self.positions(key).next().is_none() {
+            return Vec::new();
+        }
+        ExtractIf::new(&mut self.keys, &mut self.values, |k, _| key.equivalent(k))
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Retains only the entries for which `f` returns `true`, preserving their order.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        retain_in_place(&mut self.keys, &mut self.values, |_, k, v| f(k, v));
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.keys.iter().zip(self.values.iter()),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.keys.iter().zip(self.values.iter_mut()),
+        }
+    }
+
+    /// Iterates over the keys, once per stored value.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            iter: self.keys.iter(),
+            _phantom: Default::default(),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            iter: self.values.iter(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for VecMultiMap<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for VecMultiMap<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for VecMultiMap<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let iterator = iter.into_iter();
+        let mut this = Self::with_capacity(iterator.size_hint().0);
+        this.extend(iterator);
+        this
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a VecMultiMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[test]
+fn multiple_values() {
+    let mut map: VecMultiMap<&str, u32> = vec![("a", 1), ("b", 2), ("a", 3)].into_iter().collect();
+    map.insert("a", 4);
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.count(&"a"), 3);
+    assert_eq!(map.get(&"a"), Some(&1));
+    assert_eq!(
+        map.get_all(&"a").copied().collect::<Vec<_>>(),
+        vec![1, 3, 4]
+    );
+    assert_eq!(map.get_all(&"c").count(), 0);
+
+    map.insert("c", 5);
+    let capacity = map.capacity();
+    assert_eq!(map.remove_all(&"a"), vec![1, 3, 4]);
+    assert!(!map.contains_key(&"a"));
+    assert_eq!(map.len(), 2);
+    assert!(map.iter().eq(vec![(&"b", &2), (&"c", &5)]));
+    assert_eq!(map.remove_all(&"a"), Vec::<u32>::new());
+    assert_eq!(map.capacity(), capacity);
+}
@@ -0,0 +1,77 @@
+//! An optional implementation of parallel iteration and collection, through `rayon`.
+//!
+//! Parallel collection still enforces key uniqueness: pairs are gathered in parallel, then
+//! inserted sequentially in their original order, so the last value wins like with `FromIterator`.
+
+extern crate rayon;
+
+use crate::VecMap;
+use alloc::vec::Vec;
+
+use self::rayon::iter::{
+    FromParallelIterator, IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelExtend, ParallelIterator, Zip,
+};
+
+impl<'a, K: Sync, V: Sync> IntoParallelIterator for &'a VecMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type Iter = Zip<self::rayon::slice::Iter<'a, K>, self::rayon::slice::Iter<'a, V>>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.keys.par_iter().zip(self.values.par_iter())
+    }
+}
+
+impl<'a, K: Sync, V: Send> IntoParallelIterator for &'a mut VecMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type Iter = Zip<self::rayon::slice::Iter<'a, K>, self::rayon::slice::IterMut<'a, V>>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.keys.par_iter().zip(self.values.par_iter_mut())
+    }
+}
+
+impl<K: Send, V: Send> IntoParallelIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type Iter = Zip<self::rayon::vec::IntoIter<K>, self::rayon::vec::IntoIter<V>>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.keys.into_par_iter().zip(self.values.into_par_iter())
+    }
+}
+
+impl<K: PartialEq + Send, V: Send> ParallelExtend<(K, V)> for VecMap<K, V> {
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let pairs: Vec<(K, V)> = par_iter.into_par_iter().collect();
+        self.extend(pairs);
+    }
+}
+
+impl<K: PartialEq + Send, V: Send> FromParallelIterator<(K, V)> for VecMap<K, V> {
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let mut map = VecMap::new();
+        map.par_extend(par_iter);
+        map
+    }
+}
+
+#[test]
+fn parallel_collect() {
+    let pairs: Vec<(u32, u32)> = (0..1600).map(|i| (i % 100, i)).collect();
+    let sequential: VecMap<u32, u32> = pairs.iter().copied().collect();
+    let parallel: VecMap<u32, u32> = pairs.par_iter().copied().collect();
+    assert_eq!(parallel.len(), 100);
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel[&7], 1507);
+
+    let doubled: VecMap<u32, u32> = parallel.par_iter().map(|(k, v)| (*k, v * 2)).collect();
+    assert_eq!(doubled[&7], 3014);
+    let sum: u64 = parallel.into_par_iter().map(|(_, v)| v as u64).sum();
+    assert_eq!(sum, (1500..1600).sum::<u64>());
+}
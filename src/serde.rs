@@ -12,16 +12,48 @@ extern crate serde;
 use crate::set::VecSet;
 use crate::VecMap;
 
-use self::serde::de::{Error, MapAccess, SeqAccess, Visitor};
+use self::serde::de::{Error, MapAccess, SeqAccess, Unexpected, Visitor};
 use self::serde::ser::{SerializeMap, SerializeSeq};
 use self::serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use std::fmt;
-use std::marker::PhantomData;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// The most memory a size hint can make a collection allocate upfront, as hints come from the input.
+const MAX_PREALLOCATION: usize = 1024 * 1024;
+
+/// The number of entries reserved at once while deserializing a collection without a size hint.
+const CHUNK: usize = 16;
+
+/// Capacity to allocate before deserializing a collection of `(K, V)` entries with the given size hint.
+///
+/// The hint is trusted up to `MAX_PREALLOCATION` bytes, so that a malicious one can't trigger a huge
+/// allocation. Without a hint, nothing is allocated until the first entry arrives: see [`next_chunk`].
+#[inline]
+fn initial_capacity<K, V>(size_hint: Option<usize>) -> usize {
+    let entry_size = (core::mem::size_of::<K>() + core::mem::size_of::<V>()).max(1);
+    size_hint.map_or(0, |hint| hint.min(MAX_PREALLOCATION / entry_size))
+}
+
+/// The number of entries to reserve before inserting into a collection being deserialized.
+///
+/// Once the collection is full, this reserves a first chunk of `CHUNK` entries, then doubles the
+/// capacity, skipping the smallest steps of `Vec`'s one-push-at-a-time growth for formats that can't
+/// tell the collection's length upfront (such as JSON). Nothing is reserved if `is_new` tells that
+/// the entry's key is already present, as inserting it won't grow the collection; it is only called
+/// once the collection is full.
+#[inline]
+fn next_chunk<F: FnOnce() -> bool>(len: usize, capacity: usize, is_new: F) -> usize {
+    if len < capacity || !is_new() {
+        0
+    } else {
+        len.max(CHUNK)
+    }
+}
 
 impl<K, V> Serialize for VecMap<K, V>
 where
-    K: Serialize + Eq,
+    K: Serialize + PartialEq,
     V: Serialize,
 {
     #[inline]
@@ -53,7 +85,7 @@ impl<K, V> VecMapVisitor<K, V> {
 
 impl<'de, K, V> Visitor<'de> for VecMapVisitor<K, V>
 where
-    K: Deserialize<'de> + Eq,
+    K: Deserialize<'de> + PartialEq,
     V: Deserialize<'de>,
 {
     type Value = VecMap<K, V>;
@@ -75,9 +107,12 @@ where
     where
         Visitor: MapAccess<'de>,
     {
-        let mut values = VecMap::with_capacity(visitor.size_hint().unwrap_or(0));
+        let mut values = VecMap::with_capacity(initial_capacity::<K, V>(visitor.size_hint()));
 
         while let Some((key, value)) = visitor.next_entry()? {
+            values.reserve(next_chunk(values.len(), values.capacity(), || {
+                !values.contains_key(&key)
+            }));
             values.insert(key, value);
         }
 
@@ -87,7 +122,7 @@ where
 
 impl<'de, K, V> Deserialize<'de> for VecMap<K, V>
 where
-    K: Deserialize<'de> + Eq,
+    K: Deserialize<'de> + PartialEq,
     V: Deserialize<'de>,
 {
     fn deserialize<D>(deserializer: D) -> Result<VecMap<K, V>, D::Error>
@@ -98,9 +133,359 @@ where
     }
 }
 
+/// A `VecMap` whose deserialization fails on duplicate keys, instead of letting the last one win.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StrictVecMap<K, V>(pub VecMap<K, V>);
+
+#[allow(missing_docs)]
+#[derive(Default)]
+pub struct StrictVecMapVisitor<K, V> {
+    marker: PhantomData<VecMap<K, V>>,
+}
+
+impl<K, V> StrictVecMapVisitor<K, V> {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        StrictVecMapVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for StrictVecMapVisitor<K, V>
+where
+    K: Deserialize<'de> + PartialEq,
+    V: Deserialize<'de>,
+{
+    type Value = StrictVecMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a VecMap without duplicate keys")
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(StrictVecMap(VecMap::new()))
+    }
+
+    #[inline]
+    fn visit_map<Visitor>(self, mut visitor: Visitor) -> Result<Self::Value, Visitor::Error>
+    where
+        Visitor: MapAccess<'de>,
+    {
+        let mut values = VecMap::with_capacity(initial_capacity::<K, V>(visitor.size_hint()));
+
+        while let Some((key, value)) = visitor.next_entry()? {
+            values.reserve(next_chunk(values.len(), values.capacity(), || {
+                !values.contains_key(&key)
+            }));
+            if values.insert(key, value).is_some() {
+                return Err(Visitor::Error::custom("duplicate key in map"));
+            }
+        }
+
+        Ok(StrictVecMap(values))
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for StrictVecMap<K, V>
+where
+    K: Deserialize<'de> + PartialEq,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<StrictVecMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(StrictVecMapVisitor::new())
+    }
+}
+
+/// A `VecMap` whose deserialization trusts the input not to contain duplicate keys, skipping the
+/// per-entry key search that makes deserializing a `VecMap` O(n²).
+///
+/// Deserializing it never fails on duplicate keys: they are kept in the wrapped map, which can
+/// only be taken out through the `unsafe` [`TrustedVecMap::into_inner`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrustedVecMap<K, V>(VecMap<K, V>);
+
+impl<K, V> TrustedVecMap<K, V> {
+    /// Returns the deserialized map.
+    ///
+    /// # Safety
+    /// Like with [`VecMap::push_insert`], the input must not have contained duplicate keys, such as
+    /// data serialized from a `VecMap`. Otherwise, the returned map breaks its invariants.
+    pub unsafe fn into_inner(self) -> VecMap<K, V> {
+        self.0
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Default)]
+pub struct TrustedVecMapVisitor<K, V> {
+    marker: PhantomData<VecMap<K, V>>,
+}
+
+impl<K, V> TrustedVecMapVisitor<K, V> {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        TrustedVecMapVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for TrustedVecMapVisitor<K, V>
+where
+    K: Deserialize<'de> + PartialEq,
+    V: Deserialize<'de>,
+{
+    type Value = TrustedVecMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a VecMap without duplicate keys")
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(TrustedVecMap(VecMap::new()))
+    }
+
+    #[inline]
+    fn visit_map<Visitor>(self, mut visitor: Visitor) -> Result<Self::Value, Visitor::Error>
+    where
+        Visitor: MapAccess<'de>,
+    {
+        let mut values = VecMap::with_capacity(initial_capacity::<K, V>(visitor.size_hint()));
+
+        while let Some((key, value)) = visitor.next_entry()? {
+            values.reserve(next_chunk(values.len(), values.capacity(), || true));
+            unsafe { values.push_insert(key, value) };
+        }
+
+        Ok(TrustedVecMap(values))
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for TrustedVecMap<K, V>
+where
+    K: Deserialize<'de> + PartialEq,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<TrustedVecMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(TrustedVecMapVisitor::new())
+    }
+}
+
+/// Serializes a `VecMap` as a sequence of `(key, value)` pairs rather than as a map.
+///
+/// This lets maps with non-string keys round-trip through formats such as JSON,
+/// whose object keys must be strings. See [`as_seq`] for the `#[serde(with = ...)]` form.
+pub struct AsSeq<'a, K, V>(pub &'a VecMap<K, V>);
+
+impl<'a, K, V> Serialize for AsSeq<'a, K, V>
+where
+    K: Serialize + PartialEq,
+    V: Serialize,
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_seq(Some(self.0.len()))?;
+        for entry in self.0 {
+            state.serialize_element(&entry)?;
+        }
+        state.end()
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Default)]
+pub struct VecMapSeqVisitor<K, V> {
+    marker: PhantomData<VecMap<K, V>>,
+}
+
+impl<K, V> VecMapSeqVisitor<K, V> {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        VecMapSeqVisitor {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for VecMapSeqVisitor<K, V>
+where
+    K: Deserialize<'de> + PartialEq,
+    V: Deserialize<'de>,
+{
+    type Value = VecMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a sequence of key-value pairs")
+    }
+
+    #[inline]
+    fn visit_seq<Visitor>(self, mut visitor: Visitor) -> Result<Self::Value, Visitor::Error>
+    where
+        Visitor: SeqAccess<'de>,
+    {
+        let mut values = VecMap::with_capacity(initial_capacity::<K, V>(visitor.size_hint()));
+
+        while let Some((key, value)) = visitor.next_element()? {
+            values.reserve(next_chunk(values.len(), values.capacity(), || {
+                !values.contains_key(&key)
+            }));
+            values.insert(key, value);
+        }
+
+        Ok(values)
+    }
+}
+
+/// Serializes and deserializes a `VecMap` as a sequence of `(key, value)` pairs,
+/// for use with `#[serde(with = "vector_map::serde::as_seq")]`.
+pub mod as_seq {
+    use super::*;
+
+    #[allow(missing_docs)]
+    pub fn serialize<K, V, S>(map: &VecMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + PartialEq,
+        V: Serialize,
+        S: Serializer,
+    {
+        AsSeq(map).serialize(serializer)
+    }
+
+    #[allow(missing_docs)]
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<VecMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + PartialEq,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(VecMapSeqVisitor::new())
+    }
+}
+
+/// Serializes a `VecMap` with its entries in key order, making the output independent of the order they
+/// were inserted in, for use with `#[serde(serialize_with = "vector_map::serde::sorted_ser::serialize")]`.
+///
+/// The usual `Deserialize` implementation reads the result back.
+pub mod sorted_ser {
+    use super::*;
+
+    #[allow(missing_docs)]
+    pub fn serialize<K, V, S>(map: &VecMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Ord,
+        V: Serialize,
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(map.len()))?;
+        for (k, v) in map.iter_sorted() {
+            state.serialize_entry(k, v)?;
+        }
+        state.end()
+    }
+}
+
+/// Filters applied to a `VecMap`'s entries while deserializing it, for use with
+/// `#[serde(deserialize_with = "vector_map::serde::deserialize_filtered::skip_none")]`.
+pub mod deserialize_filtered {
+    use super::*;
+
+    /// Deserializes a map whose values may be null, such as `{"a": 1, "b": null}`, dropping the null entries.
+    pub fn skip_none<'de, K, V, D>(deserializer: D) -> Result<VecMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + PartialEq,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let mut map: VecMap<K, Option<V>> = Deserialize::deserialize(deserializer)?;
+        map.retain(|_, value| value.is_some());
+        Ok(map.into_map_values(|value| value.unwrap()))
+    }
+}
+
+/// Serializes and deserializes a `VecSet` as a map from each element to `true`, such as
+/// `{"a": true, "b": true}`, for use with `#[serde(with = "vector_map::serde::set_as_map")]`.
+///
+/// Deserialization rejects entries mapped to anything but `true`.
+pub mod set_as_map {
+    use super::*;
+
+    #[allow(missing_docs)]
+    pub fn serialize<K, S>(set: &VecSet<K>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + PartialEq,
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(set.len()))?;
+        for k in set {
+            state.serialize_entry(k, &true)?;
+        }
+        state.end()
+    }
+
+    #[allow(missing_docs)]
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<VecSet<K>, D::Error>
+    where
+        K: Deserialize<'de> + PartialEq,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(VecSetMapVisitor(PhantomData))
+    }
+
+    struct VecSetMapVisitor<K>(PhantomData<VecSet<K>>);
+
+    impl<'de, K> Visitor<'de> for VecSetMapVisitor<K>
+    where
+        K: Deserialize<'de> + PartialEq,
+    {
+        type Value = VecSet<K>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a map from the set's elements to true")
+        }
+
+        #[inline]
+        fn visit_map<Visitor>(self, mut visitor: Visitor) -> Result<Self::Value, Visitor::Error>
+        where
+            Visitor: MapAccess<'de>,
+        {
+            let mut values = VecSet::with_capacity(initial_capacity::<K, ()>(visitor.size_hint()));
+
+            while let Some((key, present)) = visitor.next_entry::<K, bool>()? {
+                if !present {
+                    return Err(Error::invalid_value(Unexpected::Bool(false), &"true"));
+                }
+                values.reserve(next_chunk(values.len(), values.capacity(), || {
+                    !values.contains(&key)
+                }));
+                values.insert(key);
+            }
+
+            Ok(values)
+        }
+    }
+}
+
 impl<K> Serialize for VecSet<K>
 where
-    K: Serialize + Eq,
+    K: Serialize + PartialEq,
 {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -132,7 +517,7 @@ impl<K> VecSetVisitor<K> {
 
 impl<'de, K> Visitor<'de> for VecSetVisitor<K>
 where
-    K: Deserialize<'de> + Eq,
+    K: Deserialize<'de> + PartialEq,
 {
     type Value = VecSet<K>;
 
@@ -153,9 +538,12 @@ where
     where
         Visitor: SeqAccess<'de>,
     {
-        let mut values = VecSet::with_capacity(visitor.size_hint().unwrap_or(0));
+        let mut values = VecSet::with_capacity(initial_capacity::<K, ()>(visitor.size_hint()));
 
         while let Some(key) = visitor.next_element()? {
+            values.reserve(next_chunk(values.len(), values.capacity(), || {
+                !values.contains(&key)
+            }));
             values.insert(key);
         }
 
@@ -165,7 +553,7 @@ where
 
 impl<'de, K> Deserialize<'de> for VecSet<K>
 where
-    K: Deserialize<'de> + Eq,
+    K: Deserialize<'de> + PartialEq,
 {
     fn deserialize<D>(deserializer: D) -> Result<VecSet<K>, D::Error>
     where
@@ -174,3 +562,201 @@ where
         deserializer.deserialize_seq(VecSetVisitor::new())
     }
 }
+
+#[test]
+fn as_seq_round_trip() {
+    let map: VecMap<u32, &str> = vec![(1, "a"), (20, "b")].into_iter().collect();
+    let json = serde_json::to_string(&AsSeq(&map)).unwrap();
+    assert_eq!(json, r#"[[1,"a"],[20,"b"]]"#);
+    let back: VecMap<u32, String> =
+        as_seq::deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+    assert_eq!(back.len(), 2);
+    assert_eq!(back[&20], "b");
+}
+
+#[test]
+fn duplicate_keys() {
+    let json = r#"{"a": 1, "b": 2, "a": 3}"#;
+    let map: VecMap<String, u32> = serde_json::from_str(json).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["a"], 3);
+    assert!(serde_json::from_str::<StrictVecMap<String, u32>>(json).is_err());
+
+    let map: StrictVecMap<String, u32> = serde_json::from_str(r#"{"a": 1, "b": 2}"#).unwrap();
+    assert_eq!(map.0.len(), 2);
+}
+
+#[test]
+fn float_keys() {
+    let map: VecMap<f32, u32> = vec![(0.5, 1), (1.5, 2)].into_iter().collect();
+    let json = serde_json::to_string(&AsSeq(&map)).unwrap();
+    let back: VecMap<f32, u32> =
+        as_seq::deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+    assert_eq!(back, map);
+
+    let set: VecSet<f64> = vec![0.25, 2.0].into_iter().collect();
+    let back: VecSet<f64> = serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+    assert_eq!(back, set);
+}
+
+#[test]
+fn hintless_sequence() {
+    let json = serde_json::to_string(&(0..1000).collect::<Vec<u32>>()).unwrap();
+    let set: VecSet<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(set.len(), 1000);
+    assert!(set.contains(&999));
+}
+
+#[test]
+fn preallocation() {
+    let set: VecSet<u32> = serde_json::from_str("[]").unwrap();
+    assert_eq!(set.capacity(), 0);
+    let set: VecSet<u32> = serde_json::from_str("[1]").unwrap();
+    assert!(set.capacity() >= CHUNK);
+
+    // Growing by chunks at least doubles the capacity: 16, 32, ..., 1024 entries.
+    let mut values: Vec<u32> = Vec::new();
+    let mut reallocations = 0;
+    for i in 0..1000 {
+        let capacity = values.capacity();
+        values.reserve(next_chunk(values.len(), capacity, || true));
+        if values.capacity() != capacity {
+            reallocations += 1;
+        }
+        values.push(i);
+        assert!(values.capacity() >= values.len());
+    }
+    assert!(reallocations <= 7);
+
+    // Duplicates are found before growing the collection.
+    let json = serde_json::to_string(&(0..16).collect::<Vec<u32>>()).unwrap();
+    let unique: VecSet<u32> = serde_json::from_str(&json).unwrap();
+    let json = serde_json::to_string(&(0..16).chain(0..4).collect::<Vec<u32>>()).unwrap();
+    let duplicated: VecSet<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(duplicated.len(), 16);
+    assert_eq!(duplicated.capacity(), unique.capacity());
+
+    assert_eq!(initial_capacity::<u32, u32>(None), 0);
+    assert_eq!(initial_capacity::<u32, u32>(Some(100)), 100);
+    assert_eq!(
+        initial_capacity::<u32, u32>(Some(usize::MAX)),
+        MAX_PREALLOCATION / 8
+    );
+    assert_eq!(next_chunk(0, 0, || true), CHUNK);
+    assert_eq!(next_chunk(3, 16, || true), 0);
+    assert_eq!(next_chunk(64, 64, || true), 64);
+    assert_eq!(next_chunk(64, 64, || false), 0);
+}
+
+#[test]
+fn flattened_field() {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        #[serde(flatten)]
+        extra: VecMap<String, serde_json::Value>,
+    }
+
+    let json = r#"{"id":7,"name":"seven","tags":["odd","prime"]}"#;
+    let record: Record = serde_json::from_str(json).unwrap();
+    assert_eq!(record.id, 7);
+    assert_eq!(record.extra.len(), 2);
+    assert!(!record.extra.contains_key("id"));
+    assert_eq!(record.extra["name"], "seven");
+
+    let round_trip = serde_json::to_string(&record).unwrap();
+    assert_eq!(round_trip, json);
+    assert_eq!(serde_json::from_str::<Record>(&round_trip).unwrap(), record);
+}
+
+#[test]
+fn sorted_serialization() {
+    use serde_derive::Serialize;
+
+    #[derive(Serialize)]
+    struct Golden {
+        #[serde(serialize_with = "sorted_ser::serialize")]
+        map: VecMap<String, u32>,
+    }
+
+    let forward = Golden {
+        map: (0..16).map(|i| (format!("{:02}", i), i)).collect(),
+    };
+    let backward = Golden {
+        map: (0..16).rev().map(|i| (format!("{:02}", i), i)).collect(),
+    };
+    let json = serde_json::to_string(&forward).unwrap();
+    assert_eq!(json, serde_json::to_string(&backward).unwrap());
+    assert!(json.starts_with(r#"{"map":{"00":0,"01":1,"#));
+
+    let back: VecMap<String, u32> =
+        serde_json::from_str(&serde_json::to_string(&backward.map).unwrap()).unwrap();
+    assert_eq!(back, forward.map);
+}
+
+#[test]
+fn trusted_keys() {
+    let map: VecMap<String, u32> = (0..100).map(|i| (i.to_string(), i)).collect();
+    let json = serde_json::to_string(&map).unwrap();
+    let trusted: TrustedVecMap<String, u32> = serde_json::from_str(&json).unwrap();
+    let trusted = unsafe { trusted.into_inner() };
+    assert_eq!(trusted, map);
+    assert!(trusted.keys().eq(map.keys()));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "duplicate keys")]
+fn trusted_duplicate_keys() {
+    let trusted: TrustedVecMap<String, u32> = serde_json::from_str(r#"{"a": 1, "a": 2}"#).unwrap();
+    unsafe { trusted.into_inner() }.check_invariants();
+}
+
+#[test]
+fn skip_none_values() {
+    use serde_derive::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Settings {
+        #[serde(deserialize_with = "deserialize_filtered::skip_none")]
+        values: VecMap<String, u32>,
+    }
+
+    let wire: VecMap<String, Option<u32>> = vec![
+        ("a".to_owned(), Some(1)),
+        ("b".to_owned(), None),
+        ("c".to_owned(), Some(3)),
+    ]
+    .into_iter()
+    .collect();
+    let json = format!(r#"{{"values":{}}}"#, serde_json::to_string(&wire).unwrap());
+    assert_eq!(json, r#"{"values":{"a":1,"b":null,"c":3}}"#);
+
+    let settings: Settings = serde_json::from_str(&json).unwrap();
+    assert_eq!(settings.values.len(), 2);
+    assert!(!settings.values.contains_key("b"));
+    assert!(settings.values.keys().eq(vec!["a", "c"]));
+    assert_eq!(settings.values["c"], 3);
+}
+
+#[test]
+fn set_as_map_round_trip() {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Flags {
+        #[serde(with = "set_as_map")]
+        enabled: VecSet<String>,
+    }
+
+    let flags = Flags {
+        enabled: vec!["a".to_owned(), "b".to_owned()].into_iter().collect(),
+    };
+    let json = serde_json::to_string(&flags).unwrap();
+    assert_eq!(json, r#"{"enabled":{"a":true,"b":true}}"#);
+    assert_eq!(serde_json::from_str::<Flags>(&json).unwrap(), flags);
+
+    assert!(serde_json::from_str::<Flags>(r#"{"enabled":{"a":true,"b":false}}"#).is_err());
+}
@@ -1,15 +1,30 @@
-use crate::{Keys, VecMap};
-use std::{
+use crate::{Borrowed, Equivalent, Keys, VecMap};
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
     fmt,
-    iter::{Chain, FromIterator},
-    ops::{BitAnd, BitOr, BitXor, Sub},
+    iter::{Chain, FromIterator, FusedIterator},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Sub, SubAssign},
 };
 
-#[derive(Clone)]
 pub struct VecSet<T> {
     map: VecMap<T, ()>,
 }
 
+impl<T: Clone> Clone for VecSet<T> {
+    fn clone(&self) -> Self {
+        VecSet {
+            map: self.map.clone(),
+        }
+    }
+
+    /// Reuses `self`'s allocation when its capacity suffices.
+    fn clone_from(&mut self, source: &Self) {
+        self.map.clone_from(&source.map);
+    }
+}
+
 impl<T: PartialEq> VecSet<T> {
     /// Creates an empty VecSet.
     ///
@@ -78,6 +93,36 @@ impl<T> VecSet<T> {
         self.map.reserve(additional)
     }
 
+    /// Tries to reserve capacity for at least `additional` more elements, returning an error
+    /// instead of aborting if the allocation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    /// let mut set: VecSet<i32> = VecSet::new();
+    /// assert!(set.try_reserve(10).is_ok());
+    /// assert!(set.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.map.try_reserve(additional)
+    }
+
+    /// Tries to reserve capacity for exactly `additional` more elements, returning an error
+    /// instead of aborting if the allocation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    /// let mut set: VecSet<i32> = VecSet::new();
+    /// assert!(set.try_reserve_exact(10).is_ok());
+    /// assert!(set.capacity() >= 10);
+    /// ```
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.map.try_reserve_exact(additional)
+    }
+
     /// Shrinks the capacity of the set as much as possible. It will drop
     /// down as much as possible while maintaining the internal rules
     /// and possibly leaving some space in accordance with the resize policy.
@@ -99,6 +144,29 @@ impl<T> VecSet<T> {
         self.map.shrink_to_fit()
     }
 
+    /// Shrinks the capacity of the set with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length
+    /// and the supplied value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set = VecSet::with_capacity(100);
+    /// set.insert(1);
+    /// set.insert(2);
+    /// assert!(set.capacity() >= 100);
+    /// set.shrink_to(10);
+    /// assert!(set.capacity() >= 10);
+    /// set.shrink_to(0);
+    /// assert!(set.capacity() >= 2);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.map.shrink_to(min_capacity)
+    }
+
     /// An iterator visiting all elements in arbitrary order.
     /// Iterator element type is &'a T.
     ///
@@ -122,6 +190,75 @@ impl<T> VecSet<T> {
         }
     }
 
+    /// Clones the elements into a `HashSet`, leaving the set untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    /// use std::collections::HashSet;
+    ///
+    /// let set: VecSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// let hash_set: HashSet<_> = set.to_hashset();
+    /// assert_eq!(hash_set.len(), 3);
+    /// assert!(set.iter().all(|x| hash_set.contains(x)));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_hashset(&self) -> std::collections::HashSet<T>
+    where
+        T: Eq + std::hash::Hash + Clone,
+    {
+        self.iter().cloned().collect()
+    }
+
+    /// Returns the elements as a slice, in the set's iteration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let set: VecSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// assert_eq!(set.as_slice(), &[3, 1, 2]);
+    /// assert_eq!(set.as_slice().len(), set.len());
+    /// ```
+    pub fn as_slice(&self) -> &[T] {
+        &self.map.keys
+    }
+
+    /// Consumes the set, returning its backing vector without copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let set: VecSet<_> = vec![3, 1, 2, 1].into_iter().collect();
+    /// assert_eq!(set.into_vec(), vec![3, 1, 2]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.map.keys
+    }
+
+    /// Consumes the set, yielding its elements in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let set: VecSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// let sorted: Vec<_> = set.into_iter_sorted().collect();
+    /// assert_eq!(sorted, vec![1, 2, 3]);
+    /// ```
+    pub fn into_iter_sorted(mut self) -> IntoIter<T>
+    where
+        T: Ord,
+    {
+        self.map.keys.sort_unstable();
+        self.into_iter()
+    }
+
     /// Visit the values representing the difference.
     ///
     /// # Examples
@@ -237,6 +374,37 @@ impl<T> VecSet<T> {
         }
     }
 
+    /// Consumes both sets and returns their union, moving the elements instead of cloning them.
+    ///
+    /// The elements of `self` come first, followed by those of `other` that weren't in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let a: VecSet<String> = vec!["a".to_owned(), "b".to_owned()].into_iter().collect();
+    /// let b: VecSet<String> = vec!["b".to_owned(), "c".to_owned()].into_iter().collect();
+    ///
+    /// let union: Vec<String> = a.into_union(b).into_iter().collect();
+    /// assert_eq!(union, vec!["a", "b", "c"]);
+    /// ```
+    pub fn into_union(mut self, other: VecSet<T>) -> VecSet<T>
+    where
+        T: PartialEq,
+    {
+        let len = self.len();
+        self.reserve(other.len());
+        for value in other {
+            // `other`'s elements are unique, so only `self`'s original elements need checking.
+            if !self.map.keys[..len].contains(&value) {
+                self.map.keys.push(value);
+                self.map.values.push(());
+            }
+        }
+        self
+    }
+
     /// Returns the number of elements in the set.
     ///
     /// # Examples
@@ -271,6 +439,21 @@ impl<T> VecSet<T> {
         self.map.is_empty()
     }
 
+    /// Returns the number of elements for which `f` returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let set: VecSet<_> = (1..10).collect();
+    /// assert_eq!(set.count(|x| x % 2 == 0), 4);
+    /// assert_eq!(set.count(|x| *x > 10), 0);
+    /// ```
+    pub fn count<F: FnMut(&T) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|x| f(x)).count()
+    }
+
     /// Clears the set, returning all elements in an iterator.
     #[inline]
     pub fn drain(&mut self) -> Drain<T> {
@@ -306,6 +489,118 @@ impl<T> VecSet<T> {
         self.map.retain(|k, _| f(k));
     }
 
+    /// Retains only the elements that are also in `other`, turning `self` into
+    /// the intersection of both sets without allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![2, 3, 4].into_iter().collect();
+    /// a.retain_in(&b);
+    /// assert_eq!(a, vec![2, 3].into_iter().collect());
+    /// ```
+    pub fn retain_in(&mut self, other: &VecSet<T>)
+    where
+        T: PartialEq,
+    {
+        self.retain(|v| other.contains(v));
+    }
+
+    /// Retains only the elements that are not in `other`, turning `self` into
+    /// the difference of both sets without allocating a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![2, 3, 4].into_iter().collect();
+    /// a.retain_not_in(&b);
+    /// assert_eq!(a, vec![1].into_iter().collect());
+    /// ```
+    pub fn retain_not_in(&mut self, other: &VecSet<T>)
+    where
+        T: PartialEq,
+    {
+        self.retain(|v| !other.contains(v));
+    }
+
+    /// Removes the elements that are in `other`, like [`retain_not_in`](#method.retain_not_in),
+    /// returning how many were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = (0..10).collect();
+    /// let b: VecSet<_> = (5..15).collect();
+    /// assert_eq!(a.difference_update(&b), 5);
+    /// assert_eq!(a.len(), 5);
+    /// assert_eq!(a.difference_update(&b), 0);
+    /// ```
+    pub fn difference_update(&mut self, other: &VecSet<T>) -> usize
+    where
+        T: PartialEq,
+    {
+        let len = self.len();
+        self.retain_not_in(other);
+        len - self.len()
+    }
+
+    /// Removes the elements that aren't in `other`, like [`retain_in`](#method.retain_in),
+    /// returning how many were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = (0..10).collect();
+    /// let b: VecSet<_> = (2..15).collect();
+    /// assert_eq!(a.intersection_update(&b), 2);
+    /// assert_eq!(a.len(), 8);
+    /// assert_eq!(a.intersection_update(&b), 0);
+    /// ```
+    pub fn intersection_update(&mut self, other: &VecSet<T>) -> usize
+    where
+        T: PartialEq,
+    {
+        let len = self.len();
+        self.retain_in(other);
+        len - self.len()
+    }
+
+    /// Returns an iterator that removes and yields the elements for which `f` returns `true`.
+    ///
+    /// The remaining elements keep their order. If the iterator is dropped
+    /// before being fully consumed, the elements it didn't visit are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set: VecSet<_> = (0..8).collect();
+    /// let evens: Vec<_> = set.extract_if(|x| x % 2 == 0).collect();
+    /// assert_eq!(evens, vec![0, 2, 4, 6]);
+    /// assert_eq!(set, [1, 3, 5, 7].iter().cloned().collect());
+    ///
+    /// // Stopping early leaves the unvisited elements in place.
+    /// assert_eq!(set.extract_if(|_| true).next(), Some(1));
+    /// assert_eq!(set, [3, 5, 7].iter().cloned().collect());
+    /// ```
+    pub fn extract_if<'a, F>(&'a mut self, mut f: F) -> impl Iterator<Item = T> + 'a
+    where
+        F: FnMut(&T) -> bool + 'a,
+    {
+        self.map.extract_if(move |k, _| f(k)).map(|(k, _)| k)
+    }
+
     /// Returns `true` if the set contains a value.
     ///
     /// The value may be any borrowed form of the set's value type, but
@@ -321,10 +616,60 @@ impl<T> VecSet<T> {
     /// assert_eq!(set.contains(&1), true);
     /// assert_eq!(set.contains(&4), false);
     /// ```
-    pub fn contains<Q: PartialEq<T> + ?Sized>(&self, value: &Q) -> bool {
+    pub fn contains<Q: Equivalent<T> + ?Sized>(&self, value: &Q) -> bool {
         self.map.contains_key(value)
     }
 
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// #[derive(Debug)]
+    /// struct Tagged(u32, &'static str);
+    /// impl PartialEq for Tagged {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.0 == other.0
+    ///     }
+    /// }
+    ///
+    /// let set: VecSet<_> = vec![Tagged(1, "stored")].into_iter().collect();
+    /// assert_eq!(set.get(&Tagged(1, "query")).unwrap().1, "stored");
+    /// assert!(set.get(&Tagged(2, "query")).is_none());
+    /// ```
+    pub fn get<Q: Equivalent<T> + ?Sized>(&self, value: &Q) -> Option<&T> {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// Returns `true` if the set contains a value, comparing through the
+    /// set's value type's [`Borrow`] implementation, like `HashSet::contains`.
+    ///
+    /// This is useful for values that `PartialEq` can't compare to the
+    /// stored type, such as slices for boxed slices. It is a shorthand for
+    /// [`contains`](#method.contains) with a [`Borrowed`] query.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let set: VecSet<String> = vec!["a".to_owned()].into_iter().collect();
+    /// assert!(set.contains_borrowed("a"));
+    /// assert!(!set.contains_borrowed("b"));
+    ///
+    /// let set: VecSet<Box<[u8]>> = vec![vec![1, 2].into_boxed_slice()].into_iter().collect();
+    /// assert!(set.contains_borrowed(&[1, 2][..]));
+    /// assert!(!set.contains_borrowed(&[1][..]));
+    /// ```
+    pub fn contains_borrowed<Q: PartialEq + ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.contains(&Borrowed(value))
+    }
+
     /// Returns `true` if the set has no elements in common with `other`.
     /// This is equivalent to checking for an empty intersection.
     ///
@@ -350,6 +695,36 @@ impl<T> VecSet<T> {
         self.iter().all(|v| !other.contains(v))
     }
 
+    /// Returns the Jaccard similarity of both sets: the size of their intersection divided by the
+    /// size of their union, without building either.
+    ///
+    /// Two empty sets are considered identical, with a similarity of `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let a: VecSet<_> = (0..4).collect();
+    /// let b: VecSet<_> = (2..6).collect();
+    /// assert_eq!(a.jaccard_similarity(&b), 2.0 / 6.0);
+    /// assert_eq!(a.jaccard_similarity(&a), 1.0);
+    /// assert_eq!(a.jaccard_similarity(&(4..8).collect()), 0.0);
+    /// assert_eq!(VecSet::<u32>::new().jaccard_similarity(&VecSet::new()), 1.0);
+    /// ```
+    pub fn jaccard_similarity(&self, other: &VecSet<T>) -> f64
+    where
+        T: PartialEq,
+    {
+        let intersection = self.iter().filter(|v| other.contains(*v)).count();
+        let union = self.len() + other.len() - intersection;
+        if union == 0 {
+            1.0
+        } else {
+            intersection as f64 / union as f64
+        }
+    }
+
     /// Returns `true` if the set is a subset of another.
     ///
     /// # Examples
@@ -427,6 +802,91 @@ impl<T> VecSet<T> {
         self.map.insert(value, ()).is_none()
     }
 
+    /// Adds a value to the set like [`insert`](#method.insert), returning its index in
+    /// [`as_slice`](#method.as_slice) along with whether it was newly inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set = VecSet::new();
+    /// assert_eq!(set.insert_full("a"), (0, true));
+    /// assert_eq!(set.insert_full("b"), (1, true));
+    /// assert_eq!(set.insert_full("a"), (0, false));
+    /// assert_eq!(set.as_slice()[1], "b");
+    /// ```
+    pub fn insert_full(&mut self, value: T) -> (usize, bool)
+    where
+        T: PartialEq,
+    {
+        let (index, previous) = self.map.insert_full(value, ());
+        (index, previous.is_none())
+    }
+
+    /// Adds a value to the set, replacing the existing element, if any, that is equal to it.
+    /// Returns the replaced element.
+    ///
+    /// The new element takes the replaced one's place in the set's order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set = VecSet::new();
+    /// assert_eq!(set.replace(String::from("a")), None);
+    /// assert_eq!(set.replace(String::from("a")), Some(String::from("a")));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn replace(&mut self, value: T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        match self.map.position(&value) {
+            Some(index) => Some(core::mem::replace(&mut self.map.keys[index], value)),
+            None => {
+                self.map.keys.push(value);
+                self.map.values.push(());
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the element in the set that is equal to `value`,
+    /// inserting the result of `make(value)` first if there is none.
+    ///
+    /// `make` should build an element that is equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set: VecSet<String> = VecSet::new();
+    /// let a: *const String = set.get_or_insert_with("a", str::to_owned);
+    /// assert_eq!(set.len(), 1);
+    ///
+    /// let again: *const String = set.get_or_insert_with("a", |_| unreachable!());
+    /// assert_eq!(a, again);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn get_or_insert_with<Q, F>(&mut self, value: &Q, make: F) -> &T
+    where
+        Q: Equivalent<T> + ?Sized,
+        F: FnOnce(&Q) -> T,
+    {
+        let index = match self.map.position(value) {
+            Some(index) => index,
+            None => {
+                self.map.keys.push(make(value));
+                self.map.values.push(());
+                self.map.len() - 1
+            }
+        };
+        &self.map.keys[index]
+    }
+
     /// Removes a value from the set. Returns `true` if the value was
     /// present in the set.
     ///
@@ -446,9 +906,25 @@ impl<T> VecSet<T> {
     /// assert_eq!(set.remove(&2), false);
     /// ```
 
-    pub fn remove<Q: PartialEq<T> + ?Sized>(&mut self, value: &Q) -> bool {
+    pub fn remove<Q: Equivalent<T> + ?Sized>(&mut self, value: &Q) -> bool {
         self.map.remove(value).is_some()
     }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut set: VecSet<String> = vec!["a".to_owned()].into_iter().collect();
+    /// assert_eq!(set.take("a"), Some("a".to_owned()));
+    /// assert_eq!(set.take("a"), None);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn take<Q: Equivalent<T> + ?Sized>(&mut self, value: &Q) -> Option<T> {
+        self.map.remove_entry(value).map(|(k, _)| k)
+    }
 }
 
 impl<T> PartialEq for VecSet<T>
@@ -517,9 +993,36 @@ where
     }
 }
 
+/// Accesses an element by its position in the set's iteration order.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds, like indexing a `Vec` does.
+impl<T> Index<usize> for VecSet<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.map.keys[index]
+    }
+}
+
+/// Moves the map's keys into a set, without reallocating.
+impl<T: PartialEq> From<VecMap<T, ()>> for VecSet<T> {
+    fn from(map: VecMap<T, ()>) -> Self {
+        VecSet { map }
+    }
+}
+
+/// Moves the set's elements into a map, without reallocating.
+impl<T: PartialEq> From<VecSet<T>> for VecMap<T, ()> {
+    fn from(set: VecSet<T>) -> Self {
+        set.map
+    }
+}
+
 impl<K: PartialEq> From<VecSet<K>> for Vec<K> {
     fn from(val: VecSet<K>) -> Self {
-        val.map.keys
+        val.into_vec()
     }
 }
 
@@ -550,7 +1053,7 @@ where
     /// assert_eq!(i, expected.len());
     /// ```
     fn bitor(self, rhs: &VecSet<T>) -> VecSet<T> {
-        self.union(rhs).cloned().collect()
+        self.union(rhs).collect_set()
     }
 }
 
@@ -581,7 +1084,7 @@ where
     /// assert_eq!(i, expected.len());
     /// ```
     fn bitand(self, rhs: &VecSet<T>) -> VecSet<T> {
-        self.intersection(rhs).cloned().collect()
+        self.intersection(rhs).collect_set()
     }
 }
 
@@ -612,7 +1115,7 @@ where
     /// assert_eq!(i, expected.len());
     /// ```
     fn bitxor(self, rhs: &VecSet<T>) -> VecSet<T> {
-        self.symmetric_difference(rhs).cloned().collect()
+        self.symmetric_difference(rhs).collect_set()
     }
 }
 
@@ -643,10 +1146,140 @@ where
     /// assert_eq!(i, expected.len());
     /// ```
     fn sub(self, rhs: &VecSet<T>) -> VecSet<T> {
-        self.difference(rhs).cloned().collect()
+        self.difference(rhs).collect_set()
+    }
+}
+
+impl<T> BitOrAssign<&VecSet<T>> for VecSet<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Adds the elements of `rhs` that are missing from `self`, in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![3, 4, 5].into_iter().collect();
+    ///
+    /// a |= &b;
+    /// assert_eq!(a, vec![1, 2, 3, 4, 5].into_iter().collect());
+    /// ```
+    fn bitor_assign(&mut self, rhs: &VecSet<T>) {
+        for value in rhs {
+            if !self.contains(value) {
+                self.map.keys.push(value.clone());
+                self.map.values.push(());
+            }
+        }
+    }
+}
+
+impl<T> BitAndAssign<&VecSet<T>> for VecSet<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Removes the elements of `self` that are missing from `rhs`, in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![2, 3, 4].into_iter().collect();
+    ///
+    /// a &= &b;
+    /// assert_eq!(a, vec![2, 3].into_iter().collect());
+    /// ```
+    fn bitand_assign(&mut self, rhs: &VecSet<T>) {
+        self.retain_in(rhs);
     }
 }
 
+impl<T> BitXorAssign<&VecSet<T>> for VecSet<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Removes the elements of `self` that are in `rhs`, and adds those of `rhs`
+    /// that weren't in `self`, in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![3, 4, 5].into_iter().collect();
+    ///
+    /// a ^= &b;
+    /// assert_eq!(a, vec![1, 2, 4, 5].into_iter().collect());
+    /// ```
+    fn bitxor_assign(&mut self, rhs: &VecSet<T>) {
+        let mut keep = alloc::vec![true; self.len()];
+        let mut missing = Vec::new();
+        for value in rhs {
+            match self.map.position(value) {
+                Some(index) => keep[index] = false,
+                None => missing.push(value),
+            }
+        }
+        self.map.retain_flagged(&keep);
+        self.map.reserve(missing.len());
+        for value in missing {
+            self.map.keys.push(value.clone());
+            self.map.values.push(());
+        }
+    }
+}
+
+impl<T> SubAssign<&VecSet<T>> for VecSet<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Removes the elements of `rhs` from `self`, in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vector_map::set::VecSet;;
+    ///
+    /// let mut a: VecSet<_> = vec![1, 2, 3].into_iter().collect();
+    /// let b: VecSet<_> = vec![3, 4, 5].into_iter().collect();
+    ///
+    /// a -= &b;
+    /// assert_eq!(a, vec![1, 2].into_iter().collect());
+    /// ```
+    fn sub_assign(&mut self, rhs: &VecSet<T>) {
+        self.retain_not_in(rhs);
+    }
+}
+
+macro_rules! impl_collect_set {
+    ($($typ:ident),*) => {$(
+        impl<'a, T> $typ<'a, T>
+        where
+            T: PartialEq + Clone,
+        {
+            /// Clones the visited values into a new `VecSet`.
+            ///
+            /// Since they come from sets, the values are already known to be
+            /// unique, which spares the usual search on insertion.
+            pub fn collect_set(self) -> VecSet<T> {
+                let mut set = VecSet::with_capacity(self.size_hint().0);
+                for value in self {
+                    // The operands' elements are unique, and set operations never yield an element twice.
+                    unsafe { set.map.push_insert(value.clone(), ()) };
+                }
+                set
+            }
+        }
+    )*};
+}
+impl_collect_set!(Intersection, Difference, SymmetricDifference, Union);
+
 /// VecSet iterator
 pub struct Iter<'a, K: 'a> {
     iter: Keys<'a, K, ()>,
@@ -743,6 +1376,14 @@ where
     }
 }
 
+impl<'a, K> FusedIterator for Iter<'a, K> {}
+impl<K> FusedIterator for IntoIter<K> {}
+impl<'a, K> FusedIterator for Drain<'a, K> {}
+impl<'a, T: PartialEq> FusedIterator for Intersection<'a, T> {}
+impl<'a, T: PartialEq> FusedIterator for Difference<'a, T> {}
+impl<'a, T: PartialEq> FusedIterator for SymmetricDifference<'a, T> {}
+impl<'a, T: PartialEq> FusedIterator for Union<'a, T> {}
+
 impl<'a, K> Clone for Iter<'a, K> {
     fn clone(&self) -> Iter<'a, K> {
         Iter {
@@ -760,6 +1401,11 @@ impl<'a, K> Iterator for Iter<'a, K> {
         self.iter.size_hint()
     }
 }
+impl<'a, K> DoubleEndedIterator for Iter<'a, K> {
+    fn next_back(&mut self) -> Option<&'a K> {
+        self.iter.next_back()
+    }
+}
 impl<'a, K> ExactSizeIterator for Iter<'a, K> {
     fn len(&self) -> usize {
         self.iter.len()
@@ -832,6 +1478,24 @@ where
     }
 }
 
+impl<'a, T> DoubleEndedIterator for Intersection<'a, T>
+where
+    T: PartialEq,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next_back() {
+                None => return None,
+                Some(elt) => {
+                    if self.other.contains(elt) {
+                        return Some(elt);
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl<'a, T> Clone for Difference<'a, T>
 where
     T: PartialEq,
@@ -869,6 +1533,24 @@ where
     }
 }
 
+impl<'a, T> DoubleEndedIterator for Difference<'a, T>
+where
+    T: PartialEq,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next_back() {
+                None => return None,
+                Some(elt) => {
+                    if !self.other.contains(elt) {
+                        return Some(elt);
+                    }
+                }
+            }
+        }
+    }
+}
+
 impl<'a, T> Clone for SymmetricDifference<'a, T>
 where
     T: PartialEq,
@@ -894,6 +1576,15 @@ where
     }
 }
 
+impl<'a, T> DoubleEndedIterator for SymmetricDifference<'a, T>
+where
+    T: PartialEq,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.iter.next_back()
+    }
+}
+
 impl<'a, T> Clone for Union<'a, T>
 where
     T: PartialEq,
@@ -919,6 +1610,15 @@ where
     }
 }
 
+impl<'a, T> DoubleEndedIterator for Union<'a, T>
+where
+    T: PartialEq,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.iter.next_back()
+    }
+}
+
 #[allow(dead_code)]
 fn assert_covariance() {
     fn set<'new>(v: VecSet<&'static str>) -> VecSet<&'new str> {
@@ -945,3 +1645,166 @@ fn assert_covariance() {
         v
     }
 }
+
+#[test]
+fn reversed_set_operations() {
+    let a: VecSet<_> = (0..10).collect();
+    let b: VecSet<_> = (5..15).collect();
+    fn check<'a, I: DoubleEndedIterator<Item = &'a i32> + Clone>(iter: I) {
+        let forward: Vec<_> = iter.clone().collect();
+        let mut backward: Vec<_> = iter.rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+    check(a.iter());
+    check(a.difference(&b));
+    check(a.intersection(&b));
+    check(a.union(&b));
+    check(a.symmetric_difference(&b));
+    assert_eq!(a.difference(&b).rev().count(), 5);
+    assert_eq!(a.union(&b).rev().count(), 15);
+}
+
+#[test]
+fn collect_set() {
+    let a: VecSet<_> = (0..10).collect();
+    let b: VecSet<_> = (5..15).collect();
+    assert_eq!(a.union(&b).collect_set(), &a | &b);
+    assert_eq!(a.union(&b).collect_set(), (0..15).collect());
+    assert_eq!(a.intersection(&b).collect_set(), (5..10).collect());
+    assert_eq!(a.difference(&b).collect_set(), (0..5).collect());
+    assert_eq!(
+        a.symmetric_difference(&b).collect_set(),
+        (0..5).chain(10..15).collect()
+    );
+}
+
+#[test]
+fn retain_in_other_set() {
+    let a: VecSet<_> = (0..10).collect();
+    let b: VecSet<_> = (5..15).collect();
+
+    let mut intersection = a.clone();
+    intersection.retain_in(&b);
+    assert_eq!(intersection, &a & &b);
+
+    let mut difference = a.clone();
+    difference.retain_not_in(&b);
+    assert_eq!(difference, &a - &b);
+    assert_eq!(
+        difference.iter().copied().collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn assigning_operators() {
+    let a: VecSet<_> = (0..10).collect();
+    let b: VecSet<_> = (5..15).collect();
+
+    let mut union = a.clone();
+    union |= &b;
+    assert_eq!(union, &a | &b);
+
+    let mut intersection = a.clone();
+    intersection &= &b;
+    assert_eq!(intersection, &a & &b);
+
+    let mut symmetric_difference = a.clone();
+    symmetric_difference ^= &b;
+    assert_eq!(symmetric_difference, &a ^ &b);
+
+    let mut difference = a.clone();
+    difference -= &b;
+    assert_eq!(difference, &a - &b);
+
+    let mut same = a.clone();
+    same ^= &a;
+    assert!(same.is_empty());
+}
+
+#[test]
+fn into_union() {
+    #[derive(Debug, PartialEq)]
+    struct NoClone(u32);
+
+    let a: VecSet<_> = (0..10).map(NoClone).collect();
+    let b: VecSet<_> = (5..15).map(NoClone).collect();
+    let union = a.into_union(b);
+    assert_eq!(union.len(), 15);
+    assert!(union.iter().map(|v| v.0).eq(0..15));
+
+    let empty = VecSet::new().into_union((0..3).map(NoClone).collect());
+    assert_eq!(empty, (0..3).map(NoClone).collect());
+}
+
+#[test]
+fn map_conversions() {
+    let mut map: VecMap<u32, ()> = VecMap::with_capacity(32);
+    map.extend((0..8).map(|i| (i, ())));
+    let capacity = map.capacity();
+    let set = VecSet::from(map);
+    assert_eq!(set.capacity(), capacity);
+    assert!(set.iter().copied().eq(0..8));
+
+    let map = VecMap::from(set);
+    assert_eq!(map.capacity(), capacity);
+    assert!(map.keys().copied().eq(0..8));
+}
+
+#[test]
+fn into_iter_sorted() {
+    use alloc::collections::BTreeSet;
+    let values: Vec<u32> = (0..32).map(|i| (i * 7) % 32).collect();
+    let set: VecSet<u32> = values.iter().copied().collect();
+    let btree_set: BTreeSet<u32> = values.into_iter().collect();
+    assert!(set.into_iter_sorted().eq(btree_set));
+}
+
+#[test]
+fn positional_index() {
+    let set: VecSet<_> = vec![3, 1, 2].into_iter().collect();
+    for (i, value) in set.iter().enumerate() {
+        assert_eq!(&set[i], value);
+    }
+    assert_eq!(set[0], 3);
+}
+
+#[test]
+#[should_panic]
+fn positional_index_out_of_bounds() {
+    let set: VecSet<_> = vec![3, 1, 2].into_iter().collect();
+    let _ = set[3];
+}
+
+#[test]
+fn replace() {
+    #[derive(Debug)]
+    struct Interned {
+        name: &'static str,
+        generation: u32,
+    }
+    impl PartialEq for Interned {
+        fn eq(&self, other: &Self) -> bool {
+            self.name == other.name
+        }
+    }
+
+    let mut set = VecSet::new();
+    set.insert(Interned {
+        name: "a",
+        generation: 0,
+    });
+    set.insert(Interned {
+        name: "b",
+        generation: 0,
+    });
+    let old = set.replace(Interned {
+        name: "a",
+        generation: 1,
+    });
+    assert_eq!(old.map(|old| old.generation), Some(0));
+    assert_eq!(set.len(), 2);
+    assert_eq!(set[0].name, "a");
+    assert_eq!(set[0].generation, 1);
+}
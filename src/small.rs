@@ -0,0 +1,231 @@
+//! An optional `VecMap` variant whose storage lives inline, through `smallvec`.
+//!
+//! Like `VecMap`, keys and values are stored in separate vectors to keep keys packed together.
+
+extern crate smallvec;
+
+use core::{
+    iter::FromIterator,
+    ops::{Index, IndexMut},
+};
+
+use self::smallvec::SmallVec;
+use crate::{remove_at, retain_in_place, Equivalent, Storage};
+
+/// A `VecMap` whose first `N` entries are stored inline, only spilling to the heap past that.
+///
+/// This makes tiny maps allocation-free, at the cost of a bigger footprint for the map itself.
+#[derive(Clone, Default)]
+pub struct SmallVecMap<K, V, const N: usize> {
+    keys: SmallVec<[K; N]>,
+    values: SmallVec<[V; N]>,
+}
+
+impl<T, const N: usize> Storage<T> for SmallVec<[T; N]> {
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        SmallVec::as_mut_slice(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        SmallVec::truncate(self, len)
+    }
+
+    #[inline]
+    fn remove(&mut self, index: usize) -> T {
+        SmallVec::remove(self, index)
+    }
+
+    #[inline]
+    fn swap_remove(&mut self, index: usize) -> T {
+        SmallVec::swap_remove(self, index)
+    }
+}
+
+impl<K, V, const N: usize> SmallVecMap<K, V, N> {
+    pub fn new() -> Self {
+        SmallVecMap {
+            keys: SmallVec::new(),
+            values: SmallVec::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SmallVecMap {
+            keys: SmallVec::with_capacity(capacity),
+            values: SmallVec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity().min(self.values.capacity())
+    }
+
+    /// Returns `true` if the map has outgrown its inline storage and moved to the heap.
+    pub fn spilled(&self) -> bool {
+        self.keys.spilled() || self.values.spilled()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.keys.shrink_to_fit();
+        self.values.shrink_to_fit();
+    }
+
+    #[inline]
+    fn position<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> Option<usize> {
+        self.keys.iter().position(|k| key.equivalent(k))
+    }
+
+    pub fn contains_key<Q: Equivalent<K> + ?Sized>(&self, key: &Q) -> bool {
+        self.position(key).is_some()
+    }
+
+    pub fn get<'l, Q: Equivalent<K> + ?Sized>(&'l self, key: &Q) -> Option<&'l V> {
+        self.position(key).map(|p| &self.values[p])
+    }
+
+    pub fn get_mut<'l, Q: Equivalent<K> + ?Sized>(&'l mut self, key: &Q) -> Option<&'l mut V> {
+        self.position(key).map(move |p| &mut self.values[p])
+    }
+
+    pub fn insert(&mut self, key: K, mut value: V) -> Option<V>
+    where
+        K: PartialEq,
+    {
+        if let Some(position) = self.position(&key) {
+            core::mem::swap(&mut value, &mut self.values[position]);
+            Some(value)
+        } else {
+            self.keys.push(key);
+            self.values.push(value);
+            None
+        }
+    }
+
+    pub fn remove<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<V> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Removes the entry of `key`, following the same ordering rules as
+    /// [`VecMap::remove`](../struct.VecMap.html#method.remove).
+    pub fn remove_entry<Q: Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<(K, V)> {
+        let index = self.position(key)?;
+        Some(remove_at(&mut self.keys, &mut self.values, index))
+    }
+
+    /// Retains only the entries for which `f` returns `true`, preserving the order of the retained entries.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        retain_in_place(&mut self.keys, &mut self.values, |_, k, v| f(k, v));
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (&K, &mut V)> + ExactSizeIterator {
+        self.keys.iter().zip(self.values.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.keys.iter()
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.values.iter()
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug, const N: usize> core::fmt::Debug
+    for SmallVecMap<K, V, N>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, const N: usize> PartialEq for SmallVecMap<K, V, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: PartialEq, V, const N: usize> Extend<(K, V)> for SmallVecMap<K, V, N> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: PartialEq, V, const N: usize> FromIterator<(K, V)> for SmallVecMap<K, V, N> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
+
+impl<'a, Q: Equivalent<K> + ?Sized, K, V, const N: usize> Index<&'a Q> for SmallVecMap<K, V, N> {
+    type Output = V;
+    fn index(&self, key: &'a Q) -> &Self::Output {
+        self.get(key).unwrap()
+    }
+}
+
+impl<'a, Q: Equivalent<K> + ?Sized, K, V, const N: usize> IndexMut<&'a Q> for SmallVecMap<K, V, N> {
+    fn index_mut(&mut self, key: &'a Q) -> &mut Self::Output {
+        self.get_mut(key).unwrap()
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for SmallVecMap<K, V, N> {
+    type Item = (K, V);
+    type IntoIter =
+        core::iter::Zip<self::smallvec::IntoIter<[K; N]>, self::smallvec::IntoIter<[V; N]>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter().zip(self.values)
+    }
+}
+
+#[test]
+fn spill() {
+    let mut map: SmallVecMap<u32, u32, 4> = SmallVecMap::new();
+    for i in 0..4 {
+        map.insert(i, i * 2);
+    }
+    assert!(!map.spilled());
+    map.insert(0, 10);
+    assert!(!map.spilled());
+    map.insert(4, 8);
+    assert!(map.spilled());
+    assert_eq!(map.len(), 5);
+    assert_eq!(map[&0], 10);
+    assert_eq!(map[&4], 8);
+    assert_eq!(map.remove(&2), Some(4));
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.into_iter().map(|(k, _)| k).sum::<u32>(), 8);
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "smallvec")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use vector_map::small::SmallVecMap;
+
+/// Counts the allocations made by the current thread, so that tests running in parallel don't
+/// disturb each other's counts.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn no_allocation_below_inline_capacity() {
+    let before = allocations();
+    let mut map: SmallVecMap<u32, u64, 8> = SmallVecMap::new();
+    for i in 0..8 {
+        map.insert(i, u64::from(i) * 2);
+    }
+    map.insert(3, 0);
+    map.remove(&5);
+    map.insert(5, 10);
+    assert_eq!(map.len(), 8);
+    assert!(!map.spilled());
+    assert_eq!(allocations(), before);
+
+    map.insert(8, 16);
+    assert!(map.spilled());
+    assert!(allocations() > before);
+}